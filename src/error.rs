@@ -0,0 +1,80 @@
+use std::fmt::{Display, Formatter};
+
+/// Error that is returned by all fallible functions of the crate.
+#[derive(Debug)]
+pub enum Error {
+    /// Quantity has units of different dimension than the function expects.
+    IncompatibleUnits {
+        /// Name of the argument or value that has wrong units.
+        name: &'static str,
+        /// The offending quantity.
+        quantity: String,
+        /// Expected dimension in SI units.
+        expected: String,
+    },
+    /// Parameter has correct units (if any) but its value is not allowed.
+    InvalidParameter {
+        /// Name of the parameter.
+        name: &'static str,
+        /// The offending value.
+        value: String,
+        /// What is wrong with the value.
+        reason: &'static str,
+    },
+    /// Operation requires non-empty set of particles.
+    EmptySet,
+    /// Reading or writing of data failed.
+    Io(std::io::Error),
+    /// Numerical procedure could not produce meaningful result.
+    NumericalFailure(String),
+}
+
+impl Error {
+    /// Shorthand for `Error::InvalidParameter` with value formatted by `Display`.
+    pub(crate) fn invalid<V: Display>(name: &'static str, value: V, reason: &'static str) -> Error {
+        return Error::InvalidParameter {
+            name,
+            value: value.to_string(),
+            reason,
+        };
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Self::IncompatibleUnits {
+                name,
+                quantity,
+                expected,
+            } => write!(
+                f,
+                "{} has incompatible units: got {}, expected dimension {}",
+                name, quantity, expected
+            ),
+            Self::InvalidParameter {
+                name,
+                value,
+                reason,
+            } => write!(f, "invalid {} = {}: {}", name, value, reason),
+            Self::EmptySet => write!(f, "set of particles is empty"),
+            Self::Io(e) => write!(f, "I/O error: {}", e),
+            Self::NumericalFailure(description) => write!(f, "numerical failure: {}", description),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Error {
+        return Error::Io(e);
+    }
+}
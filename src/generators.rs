@@ -1,10 +1,53 @@
+use crate::error::Error;
 use crate::particles::ParticleSet;
+use crate::vector::Vector3;
+use rand::distributions::Standard;
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use std::f64::consts::PI;
 
 /// Trait that represents type of structures that are able to create sets of particles.
+///
+/// Random generators draw all their numbers from the given `Rng`, so the same seed and the same version
+/// of the crate give identical set of particles: bit-identical positions, velocities and masses (on the same platform,
+/// as floating-point functions of the standard library may differ between them) and identifiers from 0 to N - 1.
 pub trait Generator {
-    /// Actually generates the set of particles.
-    fn generate(&self) -> Result<ParticleSet, &'static str>;
+    /// Actually generates the set of particles drawing random numbers from `rng`; deterministic generators ignore it.
+    fn generate_with_rng(&self, rng: &mut dyn RngCore) -> Result<ParticleSet, Error>;
+
+    /// Generates the set of particles from the entropy of the system.
+    fn generate(&self) -> Result<ParticleSet, Error> {
+        return self.generate_with_rng(&mut ChaCha20Rng::from_entropy());
+    }
+
+    /// Generates the set of particles reproducibly: the random numbers are taken from `ChaCha20Rng` seeded with `seed`.
+    fn generate_with_seed(&self, seed: u64) -> Result<ParticleSet, Error> {
+        return self.generate_with_rng(&mut ChaCha20Rng::seed_from_u64(seed));
+    }
+}
+
+/// Isotropic unit vector; it is shared by generators of spherical systems.
+pub(crate) fn random_direction(rng: &mut dyn RngCore) -> Vector3 {
+    let cos_theta = 2. * rng.sample::<f64, _>(Standard) - 1.;
+    let sin_theta = (1. - cos_theta.powi(2)).sqrt();
+    let phi = 2. * PI * rng.sample::<f64, _>(Standard);
+
+    return Vector3::new(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta);
 }
 
 /// Creates particles according to [Plummer model](https://en.wikipedia.org/wiki/Plummer_model)
 pub mod plummer;
+/// Creates particles according to [King model](https://en.wikipedia.org/wiki/King_model) with given central potential
+pub mod king;
+/// Creates spheroids with Dehnen density profile, including Hernquist and Jaffe models
+pub mod dehnen;
+/// Creates dark matter halos with NFW density profile and exponential cutoff
+pub mod nfw;
+/// Creates spherical systems with arbitrary density profile and optional Osipkov-Merritt anisotropy
+pub mod spherical;
+/// Computes isotropic distribution functions of spherical models by Eddington inversion
+pub(crate) mod eddington;
+/// Creates binaries and hierarchical triples on Keplerian orbits
+pub mod binary;
+/// Creates the Sun, planets, Pluto and the Moon at epoch J2000.0
+pub mod solar_system;
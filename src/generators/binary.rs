@@ -0,0 +1,143 @@
+use super::Generator;
+use crate::error::Error;
+use crate::kepler::OrbitalElements;
+use crate::particles::*;
+use crate::quantity::typed::{IntoTyped, Mass, MassDimension};
+use rand::RngCore;
+
+/// Struct that handles creation of two bodies on Keplerian orbit around their centre of mass.
+pub struct Binary {
+    m1: Mass,
+    m2: Mass,
+    orbit: OrbitalElements,
+}
+
+impl Binary {
+    /// Creates new `Binary` struct with given parameters:
+    /// `m1` - mass of the primary component,
+    /// `m2` - mass of the secondary component (it may be zero),
+    /// `orbit` - elements of the orbit of the secondary relative to the primary.
+    ///
+    /// `m1` and `m2` are either typed `Mass` or dynamic quantities equivalent to `Units::kg`.
+    pub fn new(
+        m1: impl IntoTyped<f64, MassDimension>,
+        m2: impl IntoTyped<f64, MassDimension>,
+        orbit: OrbitalElements,
+    ) -> Result<Binary, Error> {
+        let m1_check = |mass: &Mass| -> bool { *mass > Mass::from_si(0.) };
+        let m2_check = |mass: &Mass| -> bool { *mass >= Mass::from_si(0.) };
+
+        let m1 = m1.into_typed("primary mass")?;
+        let m2 = m2.into_typed("secondary mass")?;
+
+        if !m1_check(&m1) {
+            return Err(Error::invalid("primary mass", m1, "must be positive"));
+        }
+        if !m2_check(&m2) {
+            return Err(Error::invalid("secondary mass", m2, "must be non-negative"));
+        }
+
+        return Ok(Binary { m1, m2, orbit });
+    }
+
+    pub fn get_orbit(&self) -> OrbitalElements {
+        return self.orbit;
+    }
+
+    /// Returns total mass of the components.
+    pub fn get_mass(&self) -> Mass {
+        return Mass::from_si(self.m1.value_si() + self.m2.value_si());
+    }
+}
+
+impl Generator for Binary {
+    fn generate_with_rng(&self, _rng: &mut dyn RngCore) -> Result<ParticleSet, Error> {
+        let mass = self.get_mass();
+        let (r, v) = self.orbit.to_state(mass)?;
+        let q1 = self.m1.value_si() / mass.value_si();
+        let q2 = self.m2.value_si() / mass.value_si();
+        let mut output = ParticleSet::new()?;
+
+        output.add_particle(Particle::new(r * -q2, v * -q2, self.m1)?);
+        output.add_particle(Particle::new(r * q1, v * q1, self.m2)?);
+
+        return Ok(output);
+    }
+}
+
+/// Struct that handles creation of hierarchical triple: inner binary and the third body
+/// on the outer orbit around its centre of mass.
+pub struct HierarchicalTriple {
+    inner: Binary,
+    m3: Mass,
+    orbit: OrbitalElements,
+}
+
+impl HierarchicalTriple {
+    /// Creates new `HierarchicalTriple` struct with given parameters:
+    /// `inner` - inner binary, its orbit has to be bound,
+    /// `m3` - mass of the third body,
+    /// `orbit` - elements of the orbit of the third body relative to the centre of mass of the inner binary,
+    /// its pericenter has to be beyond the apocenter of the inner orbit.
+    ///
+    /// `m3` is either typed `Mass` or dynamic quantity equivalent to `Units::kg`.
+    pub fn new(
+        inner: Binary,
+        m3: impl IntoTyped<f64, MassDimension>,
+        orbit: OrbitalElements,
+    ) -> Result<HierarchicalTriple, Error> {
+        let inner_check = |inner: &OrbitalElements| -> bool { inner.get_eccentricity() < 1. };
+        let m3_check = |mass: &Mass| -> bool { *mass >= Mass::from_si(0.) };
+        let orbit_check = |inner: &OrbitalElements, outer: &OrbitalElements| -> bool {
+            let apocenter = inner.get_semi_major_axis().value_si() * (1. + inner.get_eccentricity());
+
+            outer.get_pericenter_distance().value_si() > apocenter
+        };
+
+        let m3 = m3.into_typed("third mass")?;
+
+        if !inner_check(&inner.orbit) {
+            return Err(Error::invalid(
+                "inner eccentricity",
+                inner.orbit.get_eccentricity(),
+                "inner orbit must be bound",
+            ));
+        }
+        if !m3_check(&m3) {
+            return Err(Error::invalid("third mass", m3, "must be non-negative"));
+        }
+        if !orbit_check(&inner.orbit, &orbit) {
+            return Err(Error::invalid(
+                "outer pericenter distance",
+                orbit.get_pericenter_distance(),
+                "must be greater than apocenter distance of the inner orbit",
+            ));
+        }
+
+        return Ok(HierarchicalTriple { inner, m3, orbit });
+    }
+
+    pub fn get_inner(&self) -> &Binary {
+        return &self.inner;
+    }
+
+    pub fn get_orbit(&self) -> OrbitalElements {
+        return self.orbit;
+    }
+}
+
+impl Generator for HierarchicalTriple {
+    fn generate_with_rng(&self, rng: &mut dyn RngCore) -> Result<ParticleSet, Error> {
+        let inner_mass = self.inner.get_mass().value_si();
+        let mass = Mass::from_si(inner_mass + self.m3.value_si());
+        let (r, v) = self.orbit.to_state(mass)?;
+        let q_inner = inner_mass / mass.value_si();
+        let q3 = self.m3.value_si() / mass.value_si();
+
+        let mut output = self.inner.generate_with_rng(rng)?;
+        output.shift(r * -q3, v * -q3)?;
+        output.add_particle(Particle::new(r * q_inner, v * q_inner, self.m3)?);
+
+        return Ok(output);
+    }
+}
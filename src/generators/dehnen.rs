@@ -0,0 +1,192 @@
+use super::eddington::Eddington;
+use super::{random_direction, Generator};
+use crate::error::Error;
+use crate::particles::*;
+use crate::quantity::typed::{IntoTyped, Length, LengthDimension, Mass, MassDimension};
+use crate::quantity::Units;
+use rand::distributions::Open01;
+use rand::{Rng, RngCore};
+use std::f64::consts::PI;
+
+/// Nodes of the distribution function are placed from `10^-6` to `10^6` scale radii, 100 per decade.
+/// For `γ < 2` the cusp inside the first node holds less than `10^-6` of the mass; for `γ >= 2` the potential diverges
+/// and the distribution function there is extrapolated as power law, which is its asymptotic form.
+const GRID_START: f64 = -6.;
+const GRID_END: f64 = 6.;
+const NODES_PER_DECADE: usize = 100;
+
+/// Struct that handles creation of spheroid with [Dehnen (1993)](https://doi.org/10.1093/mnras/265.1.250) density profile
+/// `ρ(r) = (3 - γ) M a / (4 π r^γ (r + a)^(4 - γ))`; `γ = 1` is Hernquist model and `γ = 2` is Jaffe model.
+///
+/// Radii are drawn from the inverse of the cumulative mass profile `M(r) = M (r / (r + a))^(3 - γ)` and speeds
+/// from the isotropic distribution function, which is computed numerically by Eddington inversion.
+///
+/// The truncated spheroid is the inner part of the untruncated one, whose mass is larger than `m` by the mass
+/// outside the truncation radius, and has its velocities, except that speeds are capped at the escape speed
+/// of the truncated spheroid, so all particles are bound. It is in equilibrium only approximately, as the virial theorem
+/// includes the pressure at the truncation radius, and the cap lowers the pressure for truncation at a few scale radii.
+#[allow(non_snake_case)]
+pub struct Dehnen {
+    gamma: f64,
+    scale_radius: f64,
+    truncation_radius: f64,
+    n: usize,
+    m0: f64,
+    G: f64,
+    eddington: Eddington,
+}
+
+impl Dehnen {
+    /// Creates new `Dehnen` struct with given parameters:
+    /// `gamma` - logarithmic slope of the inner density cusp, `0 <= gamma < 3`,
+    /// `r` - scale radius,
+    /// `n` - number of particles,
+    /// `m` - whole mass of the spheroid.
+    ///
+    /// `r` and `m` are either typed `Length` and `Mass` or dynamic quantities equivalent to `Units::m` and `Units::kg`.
+    /// The spheroid is not truncated by default, see `set_truncation_radius`.
+    pub fn new(
+        gamma: f64,
+        r: impl IntoTyped<f64, LengthDimension>,
+        n: usize,
+        m: impl IntoTyped<f64, MassDimension>,
+    ) -> Result<Dehnen, Error> {
+        let gamma_check = |gamma: f64| -> bool { (0. ..3.).contains(&gamma) };
+        let rad_check = |rad: &Length| -> bool { *rad > Length::from_si(0.) && rad.value_si().is_finite() };
+        let n_check = |number: usize| -> bool { number != 0 };
+        let m_check = |mass: &Mass| -> bool { *mass > Mass::from_si(0.) };
+
+        let r = r.into_typed("radius")?;
+        let m = m.into_typed("mass")?;
+
+        if !gamma_check(gamma) {
+            return Err(Error::invalid("gamma", gamma, "must be in [0, 3)"));
+        }
+        if !rad_check(&r) {
+            return Err(Error::invalid("radius", r, "must be positive and finite"));
+        }
+        if !n_check(n) {
+            return Err(Error::invalid("number of particles", n, "must be positive"));
+        }
+        if !m_check(&m) {
+            return Err(Error::invalid("mass", m, "must be positive"));
+        }
+
+        let radii: Vec<f64> = (0..=(GRID_END - GRID_START) as usize * NODES_PER_DECADE)
+            .map(|i| 10_f64.powf(GRID_START + i as f64 / NODES_PER_DECADE as f64))
+            .collect();
+        let potentials: Vec<f64> = radii.iter().map(|r| Self::potential(gamma, *r)).collect();
+        let densities: Vec<f64> = radii.iter().map(|r| Self::density(gamma, *r)).collect();
+
+        return Ok(Dehnen {
+            gamma,
+            scale_radius: r.value_si(),
+            truncation_radius: f64::INFINITY,
+            n,
+            m0: m.value_si(),
+            G: Units::G
+                .convert()
+                .value_in_q(Units::m.pow(3.) * Units::kg.pow(-1.) * Units::s.pow(-2.)),
+            eddington: Eddington::new(&potentials, &densities)?,
+        });
+    }
+
+    /// Creates [Hernquist (1990)](https://doi.org/10.1086/168845) model, Dehnen model with `γ = 1`.
+    pub fn hernquist(
+        r: impl IntoTyped<f64, LengthDimension>,
+        n: usize,
+        m: impl IntoTyped<f64, MassDimension>,
+    ) -> Result<Dehnen, Error> {
+        return Self::new(1., r, n, m);
+    }
+
+    /// Creates [Jaffe (1983)](https://doi.org/10.1093/mnras/202.4.995) model, Dehnen model with `γ = 2`.
+    pub fn jaffe(
+        r: impl IntoTyped<f64, LengthDimension>,
+        n: usize,
+        m: impl IntoTyped<f64, MassDimension>,
+    ) -> Result<Dehnen, Error> {
+        return Self::new(2., r, n, m);
+    }
+
+    /// Sets the radius beyond which no particles are placed; it must be positive and may be infinite.
+    /// The whole mass `m` is distributed inside this radius, velocities are those of the untruncated model
+    /// with the same density inside it, capped at the escape speed of the truncated one.
+    pub fn set_truncation_radius(&mut self, r: impl IntoTyped<f64, LengthDimension>) -> Result<(), Error> {
+        let rad_check = |rad: &Length| -> bool { *rad > Length::from_si(0.) };
+
+        let r = r.into_typed("truncation radius")?;
+
+        if !rad_check(&r) {
+            return Err(Error::invalid("truncation radius", r, "must be positive"));
+        }
+
+        self.truncation_radius = r.value_si();
+
+        return Ok(());
+    }
+
+    pub fn get_truncation_radius(&self) -> Length {
+        return Length::from_si(self.truncation_radius);
+    }
+
+    pub fn get_gamma(&self) -> f64 {
+        return self.gamma;
+    }
+
+    /// Density in the units of `M / a^3`.
+    fn density(gamma: f64, r: f64) -> f64 {
+        return (3. - gamma) / (4. * PI) * r.powf(-gamma) * (1. + r).powf(gamma - 4.);
+    }
+
+    /// Relative potential in the units of `G M / a`; `expm1` keeps it accurate near `γ = 2`.
+    fn potential(gamma: f64, r: f64) -> f64 {
+        let log_ratio = (r / (1. + r)).ln();
+
+        if gamma == 2. {
+            return -log_ratio;
+        }
+
+        return -((2. - gamma) * log_ratio).exp_m1() / (2. - gamma);
+    }
+}
+
+impl Generator for Dehnen {
+    fn generate_with_rng(&self, rng: &mut dyn RngCore) -> Result<ParticleSet, Error> {
+        let exponent = 3. - self.gamma;
+        let truncation = self.truncation_radius / self.scale_radius;
+        let max_fraction = (1. + 1. / truncation).powf(-exponent);
+        // velocities of the untruncated model of mass `m0 / max_fraction`, whose potential is deeper than the one
+        // of the truncated spheroid by `outside` (in the units of `G m0 / (max_fraction a)`)
+        let velocity_scale = (self.G * self.m0 / (max_fraction * self.scale_radius)).sqrt();
+        let outside = if truncation.is_finite() {
+            Self::potential(self.gamma, truncation) - max_fraction / truncation
+        } else {
+            0.
+        };
+        let mass = self.m0 / (self.n as f64);
+        let mut output = ParticleSet::new()?;
+
+        for _ in 0..self.n {
+            // `r / (r + a)` is a power of the enclosed mass fraction; zero radius is excluded as the potential may diverge
+            let x: f64 = rng.sample(Open01);
+            let s = (max_fraction * x).powf(1. / exponent);
+            let r = s / (1. - s);
+            let potential = Self::potential(self.gamma, r);
+            let speed = loop {
+                let speed = self.eddington.sample_speed(potential, rng);
+
+                if speed.powi(2) / 2. < potential - outside {
+                    break speed;
+                }
+            };
+
+            let position = random_direction(rng) * (r * self.scale_radius);
+            let velocity = random_direction(rng) * (speed * velocity_scale);
+
+            output.add_particle(Particle::new(position * Units::m, velocity * Units::ms, mass * Units::kg)?);
+        }
+
+        return Ok(output);
+    }
+}
@@ -0,0 +1,347 @@
+use super::random_direction;
+use crate::error::Error;
+use crate::vector::Vector3;
+use rand::distributions::{Open01, Standard};
+use rand::{Rng, RngCore};
+use std::f64::consts::PI;
+
+/// Minimal relative difference of the potential between the nodes of the distribution function.
+const RESOLUTION: f64 = 1e-6;
+
+/// Isotropic distribution function `f(ε)` of a spherical model obtained by Eddington inversion
+/// of the density as a function of the relative potential `Ψ = -Φ`:
+/// `f(ε) = 1 / (sqrt(8) π^2) (∫ (d^2ρ / dΨ^2) dΨ / sqrt(ε - Ψ) + (dρ / dΨ)(0) / sqrt(ε))`,
+/// the integral is taken from 0 to `ε`.
+///
+/// Units are arbitrary but consistent with `G = 1`.
+pub(crate) struct Eddington {
+    energies: Vec<f64>,
+    dfs: Vec<f64>,
+}
+
+impl Eddington {
+    /// Computes the distribution function from the relative potential and density at the nodes ordered outwards,
+    /// so that the potential strictly decreases to zero (which is not included) and the density does not increase.
+    ///
+    /// `dρ / dΨ` is taken as the slope between the neighbouring nodes (and between the outermost node and zero)
+    /// at the middle of the interval and is linear in between, so the integral with the singular kernel is taken exactly;
+    /// the distribution function is tabulated at the same midpoints.
+    pub(crate) fn new(potentials: &[f64], densities: &[f64]) -> Result<Eddington, Error> {
+        let order_check = |potentials: &[f64], densities: &[f64]| -> bool {
+            potentials.len() == densities.len()
+                && !potentials.is_empty()
+                && potentials[potentials.len() - 1] > 0.
+                && densities[densities.len() - 1] >= 0.
+                && (1..potentials.len()).all(|i| potentials[i] < potentials[i - 1] && densities[i] <= densities[i - 1])
+        };
+
+        if !order_check(potentials, densities) {
+            return Err(Error::NumericalFailure(
+                "potential must decrease to zero and density must not increase outwards".to_string(),
+            ));
+        }
+
+        // in a core the potential and the density are almost constant, so the slopes between too close nodes
+        // are dominated by round-off errors; such nodes are skipped
+        let mut psi = vec![0.];
+        let mut rho = vec![0.];
+
+        for i in (0..potentials.len()).rev() {
+            if potentials[i] - psi[psi.len() - 1] >= RESOLUTION * potentials[i] {
+                psi.push(potentials[i]);
+                rho.push(densities[i]);
+            }
+        }
+
+        let energies: Vec<f64> = (0..psi.len() - 1).map(|i| (psi[i] + psi[i + 1]) / 2.).collect();
+        let slopes: Vec<f64> = (0..psi.len() - 1).map(|i| (rho[i + 1] - rho[i]) / (psi[i + 1] - psi[i])).collect();
+        let curvatures: Vec<f64> = (0..energies.len() - 1)
+            .map(|i| (slopes[i + 1] - slopes[i]) / (energies[i + 1] - energies[i]))
+            .collect();
+        let df_check = |df: f64| -> bool { df >= 0. };
+        let mut dfs = vec![0.; energies.len()];
+
+        for j in 0..energies.len() {
+            let energy = energies[j];
+            let mut integral = slopes[0] / energy.sqrt();
+
+            for i in 0..j {
+                integral += 2. * curvatures[i] * ((energy - energies[i]).sqrt() - (energy - energies[i + 1]).sqrt());
+            }
+
+            dfs[j] = integral / (8_f64.sqrt() * PI.powi(2));
+
+            if !df_check(dfs[j]) {
+                return Err(Error::NumericalFailure(format!(
+                    "distribution function is negative at energy {}",
+                    energy
+                )));
+            }
+        }
+
+        return Ok(Eddington { energies, dfs });
+    }
+
+    /// Returns `f(ε)`, interpolated as power law between the nodes and extrapolated as power law beyond them.
+    pub(crate) fn distribution_function(&self, energy: f64) -> f64 {
+        if energy <= 0. {
+            return 0.;
+        }
+        if self.energies.len() == 1 {
+            return self.dfs[0];
+        }
+
+        let i = self
+            .energies
+            .partition_point(|e| *e <= energy)
+            .max(1)
+            .min(self.energies.len() - 1);
+        let (e0, e1) = (self.energies[i - 1], self.energies[i]);
+        let (f0, f1) = (self.dfs[i - 1], self.dfs[i]);
+
+        if f0 <= 0. || f1 <= 0. {
+            return (f0 + (energy - e0) / (e1 - e0) * (f1 - f0)).max(0.);
+        }
+
+        return f0 * (energy / e0).powf((f1 / f0).ln() / (e1 / e0).ln());
+    }
+
+    /// Draws speed at relative potential `potential` from `f(Ψ - v^2 / 2) v^2 dv`, i.e. energy from
+    /// `f(ε) sqrt(Ψ - ε) dε`: the interval between the nodes is chosen by its trapezoidal weight
+    /// and the energy inside it by von Neumann rejection.
+    pub(crate) fn sample_speed(&self, potential: f64, rng: &mut dyn RngCore) -> f64 {
+        let positive_check = |value: f64| -> bool { value > 0. };
+
+        if !positive_check(potential) {
+            return 0.;
+        }
+
+        let density = |energy: f64| -> f64 { self.distribution_function(energy) * (potential - energy).sqrt() };
+
+        // the density vanishes at both ends, inner nodes take the tabulated values
+        let k = self.energies.partition_point(|e| *e < potential);
+        let mut nodes = vec![0.; k + 2];
+        let mut values = vec![0.; k + 2];
+
+        for i in 0..k {
+            nodes[i + 1] = self.energies[i];
+            values[i + 1] = self.dfs[i] * (potential - self.energies[i]).sqrt();
+        }
+        nodes[k + 1] = potential;
+
+        let mut cumulative = vec![0.; nodes.len()];
+
+        for i in 1..nodes.len() {
+            cumulative[i] = cumulative[i - 1] + (values[i - 1] + values[i]) / 2. * (nodes[i] - nodes[i - 1]);
+        }
+
+        let total = cumulative[cumulative.len() - 1];
+
+        if !positive_check(total) {
+            return 0.;
+        }
+
+        let target = total * rng.sample::<f64, _>(Standard);
+        let i = cumulative.partition_point(|c| *c <= target).max(1).min(nodes.len() - 1);
+        let (low, high) = (nodes[i - 1], nodes[i]);
+        // `f` is monotonic between the nodes and `sqrt(Ψ - ε)` decreases
+        let bound = self.distribution_function(low).max(self.distribution_function(high)) * (potential - low).sqrt();
+
+        let energy = loop {
+            let energy = low + (high - low) * rng.sample::<f64, _>(Standard);
+            let y: f64 = rng.sample(Standard);
+
+            if y * bound <= density(energy) {
+                break energy;
+            }
+        };
+
+        return (2. * (potential - energy)).sqrt();
+    }
+}
+
+/// Spherical model given by the density at the nodes of logarithmic grid of radii: the mass profile and the potential
+/// are integrated numerically (the density is extended as power law inside the first and beyond the last node)
+/// and the distribution function is computed by Eddington inversion.
+///
+/// The model may be placed into an external spherical potential and may have Osipkov-Merritt anisotropy:
+/// then the distribution function depends on `Q = Ψ - v^2 / 2 - L^2 / (2 r_a^2)` and is obtained by inversion
+/// of `ρ (1 + r^2 / r_a^2)`; velocity anisotropy is `β = r^2 / (r^2 + r_a^2)`.
+///
+/// Units are arbitrary but consistent with `G = 1`.
+pub(crate) struct TabulatedModel<'a> {
+    log_radii: Vec<f64>,
+    masses: Vec<f64>,
+    potentials: Vec<f64>,
+    inner_slope: f64,
+    outer_slope: f64,
+    outer_mass: f64,
+    outer_potential: f64,
+    external: Option<&'a dyn Fn(f64) -> f64>,
+    anisotropy_radius: f64,
+    eddington: Eddington,
+}
+
+impl<'a> TabulatedModel<'a> {
+    /// `radii` must increase, `densities` must be positive; the density must be shallower than `r^-3` inside the first node
+    /// and steeper than `r^-3` beyond the last one, so that the mass is finite.
+    ///
+    /// `external` is the relative potential of external masses as a function of radius in the units where
+    /// the total mass of the model is unity; it must decrease to zero at infinity. `anisotropy_radius` is infinite
+    /// for isotropic model.
+    pub(crate) fn new(
+        radii: &[f64],
+        densities: &[f64],
+        external: Option<&'a dyn Fn(f64) -> f64>,
+        anisotropy_radius: f64,
+    ) -> Result<TabulatedModel<'a>, Error> {
+        let grid_check = |radii: &[f64], densities: &[f64]| -> bool {
+            radii.len() == densities.len()
+                && radii.len() > 1
+                && radii[0] > 0.
+                && (1..radii.len()).all(|i| radii[i] > radii[i - 1])
+                && densities.iter().all(|rho| *rho > 0. && rho.is_finite())
+        };
+
+        if !grid_check(radii, densities) {
+            return Err(Error::NumericalFailure(
+                "radii must increase and densities must be positive".to_string(),
+            ));
+        }
+
+        let n = radii.len();
+        let log_radii: Vec<f64> = radii.iter().map(|r| r.ln()).collect();
+        let slope = |i: usize| -> f64 { -(densities[i] / densities[i - 1]).ln() / (log_radii[i] - log_radii[i - 1]) };
+        let inner_slope = slope(1);
+        let outer_slope = slope(n - 1);
+
+        if inner_slope >= 3. {
+            return Err(Error::NumericalFailure("density is too steep in the centre".to_string()));
+        }
+        if outer_slope <= 3. {
+            return Err(Error::NumericalFailure("density is too shallow at the edge".to_string()));
+        }
+
+        // integrands in `ln r` of the mass and of the outer part of the potential
+        let shell = |i: usize| -> f64 { 4. * PI * radii[i].powi(3) * densities[i] };
+        let sphere = |i: usize| -> f64 { 4. * PI * radii[i].powi(2) * densities[i] };
+
+        let mut masses = vec![shell(0) / (3. - inner_slope); n];
+
+        for i in 1..n {
+            masses[i] = masses[i - 1] + (shell(i - 1) + shell(i)) / 2. * (log_radii[i] - log_radii[i - 1]);
+        }
+
+        let outer_mass = shell(n - 1) / (outer_slope - 3.);
+        let outer_potential = sphere(n - 1) / (outer_slope - 2.);
+        let mut potentials = vec![0.; n];
+        let mut outer = outer_potential;
+
+        for i in (0..n).rev() {
+            if i + 1 < n {
+                outer += (sphere(i) + sphere(i + 1)) / 2. * (log_radii[i + 1] - log_radii[i]);
+            }
+
+            potentials[i] = masses[i] / radii[i] + outer;
+        }
+
+        let mut model = TabulatedModel {
+            log_radii,
+            masses,
+            potentials,
+            inner_slope,
+            outer_slope,
+            outer_mass,
+            outer_potential,
+            external,
+            anisotropy_radius,
+            eddington: Eddington {
+                energies: Vec::new(),
+                dfs: Vec::new(),
+            },
+        };
+
+        let total_potentials: Vec<f64> = radii.iter().map(|r| model.potential(*r)).collect();
+        let augmented_densities: Vec<f64> = (0..n)
+            .map(|i| densities[i] * (1. + (radii[i] / anisotropy_radius).powi(2)))
+            .collect();
+
+        model.eddington = Eddington::new(&total_potentials, &augmented_densities)?;
+
+        return Ok(model);
+    }
+
+    pub(crate) fn total_mass(&self) -> f64 {
+        return self.masses[self.masses.len() - 1] + self.outer_mass;
+    }
+
+    /// Radius that encloses fraction `fraction` of the total mass.
+    pub(crate) fn radius(&self, fraction: f64) -> f64 {
+        let n = self.masses.len();
+        let mass = fraction * self.total_mass();
+
+        if mass <= self.masses[0] {
+            return self.log_radii[0].exp() * (mass / self.masses[0]).powf(1. / (3. - self.inner_slope));
+        }
+        if mass >= self.masses[n - 1] {
+            let outside = 1. - (mass - self.masses[n - 1]) / self.outer_mass;
+
+            return self.log_radii[n - 1].exp() * outside.powf(-1. / (self.outer_slope - 3.));
+        }
+
+        let i = self.masses.partition_point(|m| *m <= mass);
+        let t = (mass - self.masses[i - 1]) / (self.masses[i] - self.masses[i - 1]);
+
+        return (self.log_radii[i - 1] + t * (self.log_radii[i] - self.log_radii[i - 1])).exp();
+    }
+
+    /// Relative potential of the model itself at radius `r`.
+    fn self_potential(&self, r: f64) -> f64 {
+        let n = self.log_radii.len();
+        let x = r.ln();
+
+        if x <= self.log_radii[0] {
+            let exponent = 2. - self.inner_slope;
+            let depth = if exponent == 0. {
+                self.log_radii[0] - x
+            } else {
+                -(exponent * (x - self.log_radii[0])).exp_m1() / exponent
+            };
+
+            return self.potentials[0] + self.masses[0] / self.log_radii[0].exp() * depth;
+        }
+        if x >= self.log_radii[n - 1] {
+            let ratio = (x - self.log_radii[n - 1]).exp();
+            let inside = self.masses[n - 1] + self.outer_mass * (1. - ratio.powf(3. - self.outer_slope));
+
+            return inside / r + self.outer_potential * ratio.powf(2. - self.outer_slope);
+        }
+
+        let i = self.log_radii.partition_point(|log_radius| *log_radius <= x);
+        let t = (x - self.log_radii[i - 1]) / (self.log_radii[i] - self.log_radii[i - 1]);
+
+        return self.potentials[i - 1] + t * (self.potentials[i] - self.potentials[i - 1]);
+    }
+
+    /// Relative potential at radius `r` including the external one.
+    pub(crate) fn potential(&self, r: f64) -> f64 {
+        return match self.external {
+            Some(external) => self.self_potential(r) + external(r) * self.total_mass(),
+            None => self.self_potential(r),
+        };
+    }
+
+    /// Draws position from the mass profile and velocity from the distribution function at this position.
+    ///
+    /// With Osipkov-Merritt anisotropy the distribution is isotropic in `w = (v_r, v_t sqrt(1 + r^2 / r_a^2))`,
+    /// so the tangential part of isotropic `w` is scaled back.
+    pub(crate) fn sample(&self, rng: &mut dyn RngCore) -> (Vector3, Vector3) {
+        let r = self.radius(rng.sample(Open01));
+        let direction = random_direction(rng);
+        let w = random_direction(rng) * self.eddington.sample_speed(self.potential(r), rng);
+        let radial = direction * w.dot(&direction);
+        let tangential = (w - radial) / (1. + (r / self.anisotropy_radius).powi(2)).sqrt();
+
+        return (direction * r, radial + tangential);
+    }
+}
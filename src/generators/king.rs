@@ -0,0 +1,318 @@
+use super::{random_direction, Generator};
+use crate::error::Error;
+use crate::particles::*;
+use crate::quantity::typed::{IntoTyped, Length, LengthDimension, Mass, MassDimension};
+use crate::quantity::Units;
+use rand::distributions::Standard;
+use rand::{Rng, RngCore};
+use std::f64::consts::PI;
+
+/// Step of the integration of the Poisson equation in the logarithm of the dimensionless radius.
+const STEP: f64 = 1e-3;
+/// Dimensionless radius where the integration starts from the series solution.
+const START_RADIUS: f64 = 1e-4;
+/// Dimensionless radius where the integration gives up if the potential has not reached zero.
+const MAX_RADIUS: f64 = 1e+12;
+
+/// Numerical solution of the Poisson equation at the nodes: logarithm of the dimensionless radius,
+/// dimensionless potential `W` and dimensionless mass `-r^2 W'`; the last node is the tidal radius.
+struct Profile {
+    log_radii: Vec<f64>,
+    potentials: Vec<f64>,
+    masses: Vec<f64>,
+}
+
+impl Profile {
+    fn tidal_log_radius(&self) -> f64 {
+        return self.log_radii[self.log_radii.len() - 1];
+    }
+
+    fn total_mass(&self) -> f64 {
+        return self.masses[self.masses.len() - 1];
+    }
+
+    /// Linear interpolation of `values` between nodes `i - 1` and `i`.
+    fn interpolate(values: &[f64], i: usize, t: f64) -> f64 {
+        return values[i - 1] + t * (values[i] - values[i - 1]);
+    }
+}
+
+/// Struct that handles creation of [King (1966) model](https://en.wikipedia.org/wiki/King_model) -
+/// lowered isothermal sphere with distribution function `f(E) ~ exp(W) - 1`, where `W = (Φ_t - Φ) / σ^2`
+/// is the dimensionless potential that is `W0` in the centre and zero at the tidal radius.
+///
+/// Radii are measured in the units of the King (core) radius `r0 = sqrt(9 σ^2 / (4 π G ρ0))`.
+#[allow(non_snake_case)]
+pub struct King {
+    w0: f64,
+    core_radius: f64,
+    n: usize,
+    m0: f64,
+    G: f64,
+    profile: Profile,
+}
+
+impl King {
+    /// Creates new `King` struct with given parameters:
+    /// `w0` - dimensionless central potential, it sets the concentration of the cluster (typically from 1 to 15),
+    /// `n` - number of particles,
+    /// `m` - whole mass of the cluster,
+    /// `r` - King (core) radius.
+    ///
+    /// `m` and `r` are either typed `Mass` and `Length` or dynamic quantities equivalent to `Units::kg` and `Units::m`.
+    pub fn with_core_radius(
+        w0: f64,
+        n: usize,
+        m: impl IntoTyped<f64, MassDimension>,
+        r: impl IntoTyped<f64, LengthDimension>,
+    ) -> Result<King, Error> {
+        let r = r.into_typed("core radius")?;
+
+        return Self::create(w0, n, m, r, 1.);
+    }
+
+    /// Creates new `King` struct with given tidal radius `r` instead of the core one; see `with_core_radius`.
+    pub fn with_tidal_radius(
+        w0: f64,
+        n: usize,
+        m: impl IntoTyped<f64, MassDimension>,
+        r: impl IntoTyped<f64, LengthDimension>,
+    ) -> Result<King, Error> {
+        let r = r.into_typed("tidal radius")?;
+
+        return Self::create(w0, n, m, r, 0.);
+    }
+
+    /// `core_fraction` is 1 if `r` is the core radius and 0 if it is the tidal one.
+    fn create(
+        w0: f64,
+        n: usize,
+        m: impl IntoTyped<f64, MassDimension>,
+        r: Length,
+        core_fraction: f64,
+    ) -> Result<King, Error> {
+        let w0_check = |w0: f64| -> bool { w0 > 0. && w0.is_finite() };
+        let n_check = |number: usize| -> bool { number != 0 };
+        let m_check = |mass: &Mass| -> bool { *mass > Mass::from_si(0.) };
+        let rad_check = |rad: &Length| -> bool { *rad > Length::from_si(0.) && rad.value_si().is_finite() };
+
+        let m = m.into_typed("mass")?;
+
+        if !w0_check(w0) {
+            return Err(Error::invalid("central potential", w0, "must be positive and finite"));
+        }
+        if !n_check(n) {
+            return Err(Error::invalid("number of particles", n, "must be positive"));
+        }
+        if !m_check(&m) {
+            return Err(Error::invalid("mass", m, "must be positive"));
+        }
+        if !rad_check(&r) {
+            return Err(Error::invalid("radius", r, "must be positive and finite"));
+        }
+
+        let profile = Self::solve(w0)?;
+        let tidal_radius = profile.tidal_log_radius().exp();
+        let scale = core_fraction + (1. - core_fraction) * tidal_radius;
+
+        return Ok(King {
+            w0,
+            core_radius: r.value_si() / scale,
+            n,
+            m0: m.value_si(),
+            G: Units::G
+                .convert()
+                .value_in_q(Units::m.pow(3.) * Units::kg.pow(-1.) * Units::s.pow(-2.)),
+            profile,
+        });
+    }
+
+    pub fn get_central_potential(&self) -> f64 {
+        return self.w0;
+    }
+
+    pub fn get_core_radius(&self) -> Length {
+        return Length::from_si(self.core_radius);
+    }
+
+    pub fn get_tidal_radius(&self) -> Length {
+        return Length::from_si(self.core_radius * self.profile.tidal_log_radius().exp());
+    }
+
+    /// Returns concentration `log10(r_t / r0)`.
+    pub fn get_concentration(&self) -> f64 {
+        return self.profile.tidal_log_radius() / 10_f64.ln();
+    }
+
+    /// Returns mass inside radius `r` according to the numerical solution of the Poisson equation.
+    pub fn get_enclosed_mass(&self, r: impl IntoTyped<f64, LengthDimension>) -> Result<Mass, Error> {
+        let rad_check = |rad: &Length| -> bool { rad.value_si().is_finite() };
+
+        let r = r.into_typed("radius")?;
+
+        if !rad_check(&r) {
+            return Err(Error::invalid("radius", r, "must be finite"));
+        }
+
+        let r = r.value_si() / self.core_radius;
+        let profile = &self.profile;
+
+        if r <= START_RADIUS {
+            return Ok(Mass::from_si(self.m0 * 3. * r.max(0.).powi(3) / profile.total_mass()));
+        }
+
+        let x = r.ln();
+        let i = profile.log_radii.partition_point(|log_radius| *log_radius <= x);
+
+        if i == profile.log_radii.len() {
+            return Ok(Mass::from_si(self.m0));
+        }
+
+        let t = (x - profile.log_radii[i - 1]) / (profile.log_radii[i] - profile.log_radii[i - 1]);
+        let mass = Profile::interpolate(&profile.masses, i, t);
+
+        return Ok(Mass::from_si(self.m0 * mass / profile.total_mass()));
+    }
+
+    /// Density in the units of `9 σ^2 / (4 π G r0^2)` up to the normalisation constant of the distribution function,
+    /// `exp(W) erf(sqrt(W)) - sqrt(4 W / π) (1 + 2 W / 3)`. It is summed as the series of
+    /// `exp(W) erf(sqrt(W))` without its first two terms, so there is no cancellation at small `W`.
+    fn density(w: f64) -> f64 {
+        if w <= 0. {
+            return 0.;
+        }
+
+        let mut term = 4. * w.powf(2.5) / 15.;
+        let mut sum = 0.;
+        let mut k = 2.;
+
+        while term > 1e-17 * sum {
+            sum += term;
+            term *= 2. * w / (2. * k + 3.);
+            k += 1.;
+        }
+
+        return 2. / PI.sqrt() * sum;
+    }
+
+    /// Integrates the Poisson equation `(r^2 W')' = -9 r^2 ρ(W) / ρ(W0)` outwards with RK4 in `x = ln r`
+    /// until `W` reaches zero.
+    fn solve(w0: f64) -> Result<Profile, Error> {
+        let central_density = Self::density(w0);
+        // `u = dW / dx`
+        let derivatives = |x: f64, w: f64, u: f64| -> (f64, f64) {
+            (u, -u - 9. * (2. * x).exp() * Self::density(w) / central_density)
+        };
+
+        let mut x = START_RADIUS.ln();
+        let mut w = w0 - 1.5 * START_RADIUS.powi(2);
+        let mut u = -3. * START_RADIUS.powi(2);
+
+        let mut log_radii = vec![x];
+        let mut potentials = vec![w];
+        let mut masses = vec![-x.exp() * u];
+
+        while w > 0. {
+            if x > MAX_RADIUS.ln() {
+                return Err(Error::NumericalFailure(format!(
+                    "King model with W0 = {} does not reach the tidal radius",
+                    w0
+                )));
+            }
+
+            let (k1w, k1u) = derivatives(x, w, u);
+            let (k2w, k2u) = derivatives(x + STEP / 2., w + STEP / 2. * k1w, u + STEP / 2. * k1u);
+            let (k3w, k3u) = derivatives(x + STEP / 2., w + STEP / 2. * k2w, u + STEP / 2. * k2u);
+            let (k4w, k4u) = derivatives(x + STEP, w + STEP * k3w, u + STEP * k3u);
+
+            let next_w = w + STEP / 6. * (k1w + 2. * k2w + 2. * k3w + k4w);
+            let next_u = u + STEP / 6. * (k1u + 2. * k2u + 2. * k3u + k4u);
+
+            if next_w <= 0. {
+                // density vanishes at W = 0, so the solution is close to linear here
+                let t = w / (w - next_w);
+
+                x += t * STEP;
+                u += t * (next_u - u);
+                w = 0.;
+            } else {
+                x += STEP;
+                w = next_w;
+                u = next_u;
+            }
+
+            log_radii.push(x);
+            potentials.push(w);
+            masses.push(-x.exp() * u);
+        }
+
+        return Ok(Profile {
+            log_radii,
+            potentials,
+            masses,
+        });
+    }
+
+    /// Returns dimensionless radius and potential that enclose fraction `fraction` of the mass.
+    fn radius_and_potential(&self, fraction: f64) -> (f64, f64) {
+        let profile = &self.profile;
+        let mass = fraction * profile.total_mass();
+
+        if mass < profile.masses[0] {
+            let r = (mass / 3.).cbrt();
+
+            return (r, self.w0 - 1.5 * r.powi(2));
+        }
+
+        let i = profile.masses.partition_point(|m| *m <= mass).min(profile.masses.len() - 1);
+        let t = (mass - profile.masses[i - 1]) / (profile.masses[i] - profile.masses[i - 1]);
+        let x = Profile::interpolate(&profile.log_radii, i, t);
+        let w = Profile::interpolate(&profile.potentials, i, t);
+
+        return (x.exp(), w);
+    }
+
+    /// Draws speed in the units of `σ` at dimensionless potential `w`: `s = q sqrt(2 W)`,
+    /// where `q` is drawn from `g(q) = q^2 (exp(W (1 - q^2)) - 1)` by von Neumann rejection.
+    /// `g` is bounded by `(exp(W) - 1) / 4` due to the convexity of the exponent and by `exp(W - 1) / W` if `W > 1`.
+    fn sample_speed(w: f64, rng: &mut dyn RngCore) -> f64 {
+        if w <= 0. {
+            return 0.;
+        }
+
+        let mut bound = w.exp_m1() / 4.;
+
+        if w > 1. {
+            bound = bound.min((w - 1.).exp() / w);
+        }
+
+        let q = loop {
+            let q: f64 = rng.sample(Standard);
+            let y: f64 = rng.sample(Standard);
+
+            if bound * y < q.powi(2) * (w * (1. - q.powi(2))).exp_m1() {
+                break q;
+            }
+        };
+
+        return q * (2. * w).sqrt();
+    }
+}
+
+impl Generator for King {
+    fn generate_with_rng(&self, rng: &mut dyn RngCore) -> Result<ParticleSet, Error> {
+        let dispersion = (self.G * self.m0 / (self.core_radius * self.profile.total_mass())).sqrt();
+        let mass = self.m0 / (self.n as f64);
+        let mut output = ParticleSet::new()?;
+
+        for _ in 0..self.n {
+            let (r, w) = self.radius_and_potential(rng.sample(Standard));
+            let position = random_direction(rng) * (r * self.core_radius);
+            let velocity = random_direction(rng) * (Self::sample_speed(w, rng) * dispersion);
+
+            output.add_particle(Particle::new(position * Units::m, velocity * Units::ms, mass * Units::kg)?);
+        }
+
+        return Ok(output);
+    }
+}
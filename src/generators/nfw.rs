@@ -0,0 +1,180 @@
+use super::eddington::TabulatedModel;
+use super::Generator;
+use crate::error::Error;
+use crate::particles::*;
+use crate::quantity::typed::{IntoTyped, Length, LengthDimension, Mass, MassDimension};
+use crate::quantity::Units;
+use rand::RngCore;
+use std::f64::consts::PI;
+
+/// Hubble constant (km / s / Mpc) that sets the default virial radius.
+const HUBBLE_CONSTANT: f64 = 70.;
+/// Default decay length of the cutoff in the units of the virial radius.
+const DECAY_FRACTION: f64 = 0.1;
+/// Nodes of the density are placed from `10^-6` scale radii to the virial radius plus `CUTOFF_LENGTHS` decay lengths,
+/// `NODES_PER_DECADE` per decade.
+const GRID_START: f64 = -6.;
+const CUTOFF_LENGTHS: f64 = 30.;
+const NODES_PER_DECADE: f64 = 100.;
+
+/// Struct that handles creation of dark matter halo with [NFW](https://en.wikipedia.org/wiki/Navarro–Frenk–White_profile)
+/// density profile `ρ(r) = ρ_s / ((r / r_s) (1 + r / r_s)^2)` inside the virial radius `r_vir = c r_s`.
+///
+/// Beyond the virial radius the density decays exponentially (Kazantzidis, Magorrian & Moore, 2004):
+/// `ρ(r) = ρ(r_vir) (r / r_vir)^ε exp(-(r - r_vir) / r_decay)`, `ε` is chosen so that the logarithmic slope is continuous.
+/// Radii are drawn from the inverse of the cumulative mass profile and speeds from the isotropic distribution function;
+/// both are computed numerically, the latter by Eddington inversion.
+#[allow(non_snake_case)]
+pub struct NFW {
+    virial_mass: f64,
+    concentration: f64,
+    n: usize,
+    virial_radius: f64,
+    decay_radius: Option<f64>,
+    G: f64,
+}
+
+impl NFW {
+    /// Creates new `NFW` struct with given parameters:
+    /// `m` - virial mass, i.e. the mass inside the virial radius; the cutoff adds to the whole mass
+    /// (about 20% for `c = 10` and the default decay length), see `get_total_mass`,
+    /// `c` - concentration, the ratio of the virial radius to the scale one,
+    /// `n` - number of particles.
+    ///
+    /// `m` is either typed `Mass` or dynamic quantity equivalent to `Units::kg`. The virial radius is `r_200`,
+    /// inside which the mean density is 200 times the critical one for `H0 = 70 km / s / Mpc`,
+    /// see `set_virial_radius` to use another definition. The decay length is 0.1 of the virial radius by default.
+    pub fn new(m: impl IntoTyped<f64, MassDimension>, c: f64, n: usize) -> Result<NFW, Error> {
+        let m_check = |mass: &Mass| -> bool { *mass > Mass::from_si(0.) && mass.value_si().is_finite() };
+        let c_check = |c: f64| -> bool { c > 0. && c.is_finite() };
+        let n_check = |number: usize| -> bool { number != 0 };
+
+        let m = m.into_typed("virial mass")?;
+
+        if !m_check(&m) {
+            return Err(Error::invalid("virial mass", m, "must be positive and finite"));
+        }
+        if !c_check(c) {
+            return Err(Error::invalid("concentration", c, "must be positive and finite"));
+        }
+        if !n_check(n) {
+            return Err(Error::invalid("number of particles", n, "must be positive"));
+        }
+
+        #[allow(non_snake_case)]
+        let G = Units::G
+            .convert()
+            .value_in_q(Units::m.pow(3.) * Units::kg.pow(-1.) * Units::s.pow(-2.));
+        let hubble = (HUBBLE_CONSTANT * 1e-3 * Units::kms / Units::kpc).value_in_q(Units::s.pow(-1.));
+
+        return Ok(NFW {
+            virial_mass: m.value_si(),
+            concentration: c,
+            n,
+            // M = 200 ρ_crit 4 π r^3 / 3, ρ_crit = 3 H^2 / (8 π G)
+            virial_radius: (G * m.value_si() / (100. * hubble.powi(2))).cbrt(),
+            decay_radius: None,
+            G,
+        });
+    }
+
+    /// Sets the virial radius explicitly, e.g. for another overdensity or redshift.
+    pub fn set_virial_radius(&mut self, r: impl IntoTyped<f64, LengthDimension>) -> Result<(), Error> {
+        let rad_check = |rad: &Length| -> bool { *rad > Length::from_si(0.) && rad.value_si().is_finite() };
+
+        let r = r.into_typed("virial radius")?;
+
+        if !rad_check(&r) {
+            return Err(Error::invalid("virial radius", r, "must be positive and finite"));
+        }
+
+        self.virial_radius = r.value_si();
+
+        return Ok(());
+    }
+
+    pub fn get_virial_radius(&self) -> Length {
+        return Length::from_si(self.virial_radius);
+    }
+
+    /// Sets the decay length of the exponential cutoff beyond the virial radius.
+    pub fn set_decay_radius(&mut self, r: impl IntoTyped<f64, LengthDimension>) -> Result<(), Error> {
+        let rad_check = |rad: &Length| -> bool { *rad > Length::from_si(0.) && rad.value_si().is_finite() };
+
+        let r = r.into_typed("decay radius")?;
+
+        if !rad_check(&r) {
+            return Err(Error::invalid("decay radius", r, "must be positive and finite"));
+        }
+
+        self.decay_radius = Some(r.value_si());
+
+        return Ok(());
+    }
+
+    pub fn get_decay_radius(&self) -> Length {
+        return Length::from_si(self.decay_radius.unwrap_or(DECAY_FRACTION * self.virial_radius));
+    }
+
+    pub fn get_scale_radius(&self) -> Length {
+        return Length::from_si(self.virial_radius / self.concentration);
+    }
+
+    pub fn get_concentration(&self) -> f64 {
+        return self.concentration;
+    }
+
+    /// Returns mass of the whole halo including the cutoff.
+    pub fn get_total_mass(&self) -> Result<Mass, Error> {
+        return Ok(Mass::from_si(self.virial_mass * self.model()?.total_mass()));
+    }
+
+    /// Density in the units of `M_vir / r_s^3` at radius `r` in the units of `r_s`; `d` is the decay length.
+    fn density(c: f64, d: f64, r: f64) -> f64 {
+        let normalisation = 4. * PI * ((1. + c).ln() - c / (1. + c));
+
+        if r <= c {
+            return 1. / (normalisation * r * (1. + r).powi(2));
+        }
+
+        let exponent = -(1. + 3. * c) / (1. + c) + c / d;
+
+        return Self::density(c, d, c) * (r / c).powf(exponent) * (-(r - c) / d).exp();
+    }
+
+    /// Tabulated model in the units of `r_s` and `M_vir`.
+    fn model(&self) -> Result<TabulatedModel<'static>, Error> {
+        let c = self.concentration;
+        let d = self.get_decay_radius().value_si() / self.get_scale_radius().value_si();
+        let nodes = (((c + CUTOFF_LENGTHS * d).log10() - GRID_START) * NODES_PER_DECADE).ceil() as usize;
+
+        let radii: Vec<f64> = (0..=nodes)
+            .map(|i| 10_f64.powf(GRID_START + i as f64 / NODES_PER_DECADE))
+            .collect();
+        let densities: Vec<f64> = radii.iter().map(|r| Self::density(c, d, *r)).collect();
+
+        return TabulatedModel::new(&radii, &densities, None, f64::INFINITY);
+    }
+}
+
+impl Generator for NFW {
+    fn generate_with_rng(&self, rng: &mut dyn RngCore) -> Result<ParticleSet, Error> {
+        let model = self.model()?;
+        let scale_radius = self.get_scale_radius().value_si();
+        let velocity_scale = (self.G * self.virial_mass / scale_radius).sqrt();
+        let mass = self.virial_mass * model.total_mass() / (self.n as f64);
+        let mut output = ParticleSet::new()?;
+
+        for _ in 0..self.n {
+            let (position, velocity) = model.sample(rng);
+
+            output.add_particle(Particle::new(
+                position * scale_radius * Units::m,
+                velocity * velocity_scale * Units::ms,
+                mass * Units::kg,
+            )?);
+        }
+
+        return Ok(output);
+    }
+}
@@ -1,16 +1,26 @@
-use super::Generator;
+use super::{random_direction, Generator};
+use crate::error::Error;
 use crate::particles::*;
-use crate::quantity::{ScalarQuantity, Units};
+use crate::quantity::typed::{IntoTyped, Length, LengthDimension, Mass, MassDimension};
+use crate::quantity::Units;
 use crate::vector::Vector3;
 use rand::distributions::Standard;
-use rand::prelude::*;
-use rand_distr::{Distribution, Normal};
-use std::f64::consts::PI;
-
-/// Struct that handles creation of sphere with Plummer distribution
+use rand::{Rng, RngCore};
+
+/// Struct that handles creation of sphere with Plummer distribution.
+///
+/// Radii are drawn from the inverse of the cumulative mass profile and speeds are drawn from
+/// the distribution function by rejection (Aarseth, Hénon & Wielen, 1974).
+///
+/// The truncated sphere is the inner part of the untruncated one and has its velocities, except that speeds are capped
+/// at the escape speed of the truncated sphere, so all particles are bound. Such a sphere is in equilibrium only
+/// approximately: the virial theorem includes the pressure at the truncation radius `r_t`, `2 T ≈ -W + 4 π r_t^3 p(r_t)`,
+/// whose term is about 1% of `-W` for `r_t = 5 r` and is negligible for the default 10 r; the cap changes velocities
+/// only for `r_t` of a few Plummer radii (about 5% of particles for `r_t = r`, none for `r_t = 5 r`).
 #[allow(non_snake_case)]
 pub struct Plummer {
     plummer_radius: f64,
+    truncation_radius: f64,
     n: usize,
     m0: f64,
     G: f64,
@@ -21,127 +31,114 @@ impl Plummer {
     /// `r` - Plummer radius - a scale parameter that sets the size of the cluster core,
     /// `n` - number of particles,
     /// `m` - whole mass of the cluster.
-    pub fn new(r: ScalarQuantity, n: usize, m: ScalarQuantity) -> Result<Plummer, &'static str> {
-        let rad_check = |rad: &ScalarQuantity| -> bool {
-            if rad.is_compatible(Units::m.convert()) {
-                *rad >= (0. * Units::m)
-            } else {
-                false
-            }
-        };
+    ///
+    /// `r` and `m` are either typed `Length` and `Mass` or dynamic quantities equivalent to `Units::m` and `Units::kg`.
+    /// The sphere is truncated at 10 Plummer radii, see `set_truncation_radius`.
+    pub fn new(
+        r: impl IntoTyped<f64, LengthDimension>,
+        n: usize,
+        m: impl IntoTyped<f64, MassDimension>,
+    ) -> Result<Plummer, Error> {
+        let rad_check = |rad: &Length| -> bool { *rad > Length::from_si(0.) && rad.value_si().is_finite() };
         let n_check = |number: usize| -> bool { number != 0 };
-        let m_check = |mass: &ScalarQuantity| -> bool {
-            if mass.is_compatible(Units::kg.convert()) {
-                *mass > (0.0 * Units::kg)
-            } else {
-                false
-            }
-        };
+        let m_check = |mass: &Mass| -> bool { *mass > Mass::from_si(0.) };
+
+        let r = r.into_typed("radius")?;
+        let m = m.into_typed("mass")?;
 
         if !rad_check(&r) {
-            return Err("incorrect radius");
+            return Err(Error::invalid("radius", r, "must be positive and finite"));
         }
         if !n_check(n) {
-            return Err("incorrect number of particles");
+            return Err(Error::invalid("number of particles", n, "must be positive"));
         }
         if !m_check(&m) {
-            return Err("incorrect mass");
+            return Err(Error::invalid("mass", m, "must be positive"));
         }
 
         return Ok(Plummer {
-            plummer_radius: r.value_in(Units::m),
+            plummer_radius: r.value_si(),
+            truncation_radius: 10. * r.value_si(),
             n,
-            m0: m.value_in(Units::kg),
+            m0: m.value_si(),
             G: Units::G
                 .convert()
                 .value_in_q(Units::m.pow(3.) * Units::kg.pow(-1.) * Units::s.pow(-2.)),
         });
     }
 
-    fn rho(&self, r: f64) -> f64 {
-        return (3.0 * self.m0) / (4.0 * PI * self.m0.powi(3))
-            * (1.0 + (r / self.plummer_radius).powi(2)).powf(-5.0 / 2.0);
-    }
-
-    fn spherical_to_cartesian(r: f64, phi: f64, theta: f64) -> Vector3 {
-        let x = r * theta.sin() * phi.cos();
-        let y = r * theta.sin() * phi.sin();
-        let z = r * theta.cos();
-
-        return Vector3 { x, y, z };
-    }
+    /// Sets the radius beyond which no particles are placed; it must be positive and may be infinite.
+    /// The whole mass `m` is distributed inside this radius, velocities are those of the untruncated sphere
+    /// with the same density inside it, capped at the escape speed of the truncated one.
+    pub fn set_truncation_radius(&mut self, r: impl IntoTyped<f64, LengthDimension>) -> Result<(), Error> {
+        let rad_check = |rad: &Length| -> bool { *rad > Length::from_si(0.) };
 
-    fn generate_positions(&self) -> Vec<Vector3> {
-        let number_of_divisions: usize = (self.n as f32).powf(1.0 / 3.0) as usize;
-        let max_r = 10.0 * self.plummer_radius;
-        let division = 1.0 / (number_of_divisions as f64) * max_r;
-        let mut descrete_rhos = vec![0.0_f64; number_of_divisions];
+        let r = r.into_typed("truncation radius")?;
 
-        for i in 0..number_of_divisions {
-            descrete_rhos[i] = self.rho(division * (i as f64));
+        if !rad_check(&r) {
+            return Err(Error::invalid("truncation radius", r, "must be positive"));
         }
 
-        let sum: f64 = descrete_rhos.iter().sum();
-        let mut probabilities = vec![0.0_f64; number_of_divisions + 1];
+        self.truncation_radius = r.value_si();
 
-        for i in 0..number_of_divisions {
-            probabilities[i] = descrete_rhos[i] / sum;
-        }
-
-        let mut generator = StdRng::from_entropy();
-        let mut number_of_particles_in_cell = vec![0; number_of_divisions];
+        return Ok(());
+    }
 
-        for _ in 0..self.n {
-            let mut num: f64 = generator.sample(Standard);
-
-            for j in 0..number_of_divisions {
-                if num > probabilities[j] {
-                    num -= probabilities[j];
-                } else {
-                    number_of_particles_in_cell[j] += 1;
-                    break;
-                }
-            }
-        }
+    pub fn get_truncation_radius(&self) -> Length {
+        return Length::from_si(self.truncation_radius);
+    }
 
-        let mut output = Vec::<Vector3>::new();
+    /// Fraction of the mass of the untruncated sphere that lies inside radius `r`.
+    fn mass_fraction(&self, r: f64) -> f64 {
+        return (1. + (self.plummer_radius / r).powi(2)).powf(-1.5);
+    }
 
-        for i in 0..number_of_divisions {
-            let curr = number_of_particles_in_cell[i];
+    /// Radius that encloses fraction `x` of the mass of the untruncated sphere.
+    fn inverse_mass_fraction(&self, x: f64) -> f64 {
+        return self.plummer_radius / (x.powf(-2. / 3.) - 1.).sqrt();
+    }
 
-            if curr == 0 {
-                continue;
-            }
+    fn generate_positions(&self, rng: &mut dyn RngCore) -> Vec<Vector3> {
+        let max_fraction = self.mass_fraction(self.truncation_radius);
+        let mut output = Vec::with_capacity(self.n);
 
-            for j in 1..curr + 1 {
-                let phi: f64 = generator.sample(Standard);
-                let phi = phi * 2.0 * PI;
-                let theta: f64 = generator.sample(Standard);
-                let theta = theta * PI;
-                let curr_r = division * (i as f64) + division * (j as f64) / (curr as f64);
+        for _ in 0..self.n {
+            let x: f64 = rng.sample(Standard);
+            let r = self.inverse_mass_fraction(max_fraction * x);
 
-                output.push(Self::spherical_to_cartesian(curr_r, phi, theta));
-            }
+            output.push(random_direction(rng) * r);
         }
 
         return output;
     }
 
-    fn generate_velocities(&self, positions: Vec<f64>) -> Vec<Vector3> {
-        let mut uniform_distr = StdRng::from_entropy();
-        let mut output = Vec::new();
+    /// Speeds are `q v_esc(r)` where `q` is drawn from `g(q) = q^2 (1 - q^2)^3.5` by von Neumann rejection;
+    /// the maximum of `g` is below 0.1.
+    ///
+    /// The particles are the inner part of the untruncated sphere of mass `m0 / mass_fraction(r_t)`, so its escape velocity
+    /// is used: the force inside the truncation radius does not depend on the mass outside it. The potential of
+    /// the truncated sphere is shallower by `|Φ(r_t)| - G m0 / r_t`, so `q` is also rejected above `q_max`,
+    /// for which the speed equals the escape speed of the truncated sphere.
+    fn generate_velocities(&self, positions: Vec<f64>, rng: &mut dyn RngCore) -> Vec<Vector3> {
+        let mass = self.m0 / self.mass_fraction(self.truncation_radius);
+        let potential = |r: f64| -> f64 { self.G * mass / (r.powi(2) + self.plummer_radius.powi(2)).sqrt() };
+        let depth_outside = potential(self.truncation_radius) - self.G * self.m0 / self.truncation_radius;
+        let mut output = Vec::with_capacity(positions.len());
 
         for r in positions {
-            let disp = self.G * self.m0 / (6.0 * (r.powi(2) + self.plummer_radius.powi(2)).sqrt());
-            let normal_distr = Normal::new(0.0, disp.sqrt()).unwrap();
+            let escape_velocity = (2. * potential(r)).sqrt();
+            let q_max = (1. - depth_outside / potential(r)).sqrt();
 
-            let mag = normal_distr.sample(&mut rand::thread_rng()).abs() / 2.0;
-            let phi: f64 = uniform_distr.sample(Standard);
-            let phi = phi * 2.0 * PI;
-            let theta: f64 = uniform_distr.sample(Standard);
-            let theta = theta * PI;
+            let q = loop {
+                let q: f64 = rng.sample(Standard);
+                let y: f64 = rng.sample(Standard);
+
+                if q < q_max && 0.1 * y < q.powi(2) * (1. - q.powi(2)).powf(3.5) {
+                    break q;
+                }
+            };
 
-            output.push(Self::spherical_to_cartesian(mag, phi, theta));
+            output.push(random_direction(rng) * (q * escape_velocity));
         }
 
         return output;
@@ -151,9 +148,11 @@ impl Plummer {
         positions: Vec<Vector3>,
         velocities: Vec<Vector3>,
         masses: Vec<f64>,
-    ) -> Result<ParticleSet, &'static str> {
+    ) -> Result<ParticleSet, Error> {
         if (positions.len() != velocities.len()) || (positions.len() != masses.len()) {
-            return Err("generation of positions, velocities or masses went wrong");
+            return Err(Error::NumericalFailure(
+                "generation of positions, velocities or masses went wrong".to_string(),
+            ));
         }
 
         let mut output = ParticleSet::new()?;
@@ -171,10 +170,12 @@ impl Plummer {
 }
 
 impl Generator for Plummer {
-    fn generate(&self) -> Result<ParticleSet, &'static str> {
-        let positions = self.generate_positions();
-        let velocities =
-            self.generate_velocities(positions.iter().map(|r| -> f64 { r.mag() }).collect());
+    fn generate_with_rng(&self, rng: &mut dyn RngCore) -> Result<ParticleSet, Error> {
+        let positions = self.generate_positions(rng);
+        let velocities = self.generate_velocities(
+            positions.iter().map(|r| -> f64 { r.mag() }).collect(),
+            rng,
+        );
         let masses = vec![self.m0 / (self.n as f64); self.n];
 
         return Self::generate_particles(positions, velocities, masses);
@@ -0,0 +1,319 @@
+use super::Generator;
+use crate::error::Error;
+use crate::particles::*;
+use crate::quantity::{ScalarQuantity, Units};
+use crate::vector::Vector3;
+use rand::RngCore;
+
+/// Body of the Solar System that can be included into the generated set.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Body {
+    Sun,
+    Mercury,
+    Venus,
+    /// The Earth; if the Moon is not included, the Earth is placed at the Earth-Moon barycentre
+    /// and has the mass of both bodies. The same holds for Jupiter and Saturn and their satellites below.
+    Earth,
+    /// The Moon; its position is given relative to the Earth, so it requires the Earth to be included.
+    /// The same holds for the other satellites and their planets.
+    Moon,
+    Mars,
+    Jupiter,
+    Io,
+    Europa,
+    Ganymede,
+    Callisto,
+    Saturn,
+    Titan,
+    Uranus,
+    Neptune,
+    Pluto,
+}
+
+/// Gravitational parameters (km^3 / s^2) from JPL DE430 and satellite ephemerides JUP310 and SAT427;
+/// planets other than the Earth include their satellites.
+const GM_SUN: f64 = 132712440041.9394;
+const GM_MERCURY: f64 = 22031.78;
+const GM_VENUS: f64 = 324858.592;
+const GM_EARTH: f64 = 398600.435436;
+const GM_MOON: f64 = 4902.800066;
+const GM_MARS: f64 = 42828.375214;
+const GM_JUPITER: f64 = 126712764.8;
+const GM_IO: f64 = 5959.916;
+const GM_EUROPA: f64 = 3202.739;
+const GM_GANYMEDE: f64 = 9887.834;
+const GM_CALLISTO: f64 = 7179.289;
+const GM_SATURN: f64 = 37940585.2;
+const GM_TITAN: f64 = 8978.1382;
+const GM_URANUS: f64 = 5794548.6;
+const GM_NEPTUNE: f64 = 6836527.10058;
+const GM_PLUTO: f64 = 977.;
+
+/// Heliocentric positions (km) and velocities (km / s) at J2000.0 (2000-01-01 12:00 TT) in the ecliptic frame,
+/// computed from the mean orbital elements of Standish (JPL, valid 1800-2050); the Earth row is the Earth-Moon barycentre.
+/// Precision is about 10^-4 of the orbit size, which is enough for a realistic test scenario but not for astrometry.
+const PLANETS: [(Body, [f64; 3], [f64; 3]); 9] = [
+    (
+        Body::Mercury,
+        [-1.9460980614e+07, -6.6913981136e+07, -3.6799310511e+06],
+        [3.6994783269e+01, -1.1164251162e+01, -4.3075815258e+00],
+    ),
+    (
+        Body::Venus,
+        [-1.0745859729e+08, -4.8928469385e+06, 6.1358500679e+06],
+        [1.3831388346e+00, -3.5139654899e+01, -5.6006753507e-01],
+    ),
+    (
+        Body::Earth,
+        [-2.6504441615e+07, 1.4469322746e+08, -3.8663464068e+01],
+        [-2.9786500498e+01, -5.4787784897e+00, 1.4639838988e-06],
+    ),
+    (
+        Body::Mars,
+        [2.0804093390e+08, -2.0032746845e+06, -5.1553310014e+06],
+        [1.1645636752e+00, 2.6297056008e+01, 5.2224789671e-01],
+    ),
+    (
+        Body::Jupiter,
+        [5.9814029897e+08, 4.4067207999e+08, -1.5216768479e+07],
+        [-7.9163154897e+00, 1.1143287713e+01, 1.3112531710e-01],
+    ),
+    (
+        Body::Saturn,
+        [9.5963810029e+08, 9.7921791506e+08, -5.5223571195e+07],
+        [-7.4134994187e+00, 6.7416884054e+00, 1.7733084653e-01],
+    ),
+    (
+        Body::Uranus,
+        [2.1580189799e+09, -2.0551225490e+09, -3.5609247964e+07],
+        [4.6433297574e+00, 4.6119795336e+00, -4.3072706511e-02],
+    ),
+    (
+        Body::Neptune,
+        [2.5139567343e+09, -3.7388561781e+09, 1.9059248949e+07],
+        [4.4730108811e+00, 3.0619335888e+00, -1.6612149826e-01],
+    ),
+    (
+        Body::Pluto,
+        [-1.4784802728e+09, -4.1832943320e+09, 8.7532014147e+08],
+        [5.2494834010e+00, -2.6733007022e+00, -1.2322841924e+00],
+    ),
+];
+
+/// Planetocentric positions (km) and velocities (km / s) of the satellites at the same epoch and in the same frame:
+/// the Moon is computed from its mean elements (Meeus), the other satellites from the mean elements of JPL
+/// referred to their local Laplace planes. The latter give realistic orbits, but their phases are approximate.
+const SATELLITES: [(Body, Body, [f64; 3], [f64; 3]); 6] = [
+    (
+        Body::Moon,
+        Body::Earth,
+        [-2.9322752978e+05, -2.6952048570e+05, 3.5550166409e+04],
+        [6.3772345905e-01, -7.4951150484e-01, -8.2591772449e-03],
+    ),
+    (
+        Body::Io,
+        Body::Jupiter,
+        [3.9966184415e+05, 1.2975157370e+05, 1.0490118937e+04],
+        [-5.4124296908e+00, 1.6519943245e+01, 5.1308565839e-01],
+    ),
+    (
+        Body::Europa,
+        Body::Jupiter,
+        [-5.6165602212e+05, -3.5607947419e+05, -1.7859719462e+04],
+        [7.4517278210e+00, -1.1684376023e+01, -2.0377026553e-01],
+    ),
+    (
+        Body::Ganymede,
+        Body::Jupiter,
+        [-8.2034334490e+05, -6.8541082410e+05, -3.3674736085e+04],
+        [6.9901681133e+00, -8.3451520234e+00, -2.3050157464e-01],
+    ),
+    (
+        Body::Callisto,
+        Body::Jupiter,
+        [3.2858043105e+05, 1.8521077327e+06, 6.6235285755e+04],
+        [-8.0684803001e+00, 1.4919051132e+00, -6.0297542528e-02],
+    ),
+    (
+        Body::Titan,
+        Body::Saturn,
+        [-9.4982834811e+05, 7.6408425369e+05, -3.0112704977e+05],
+        [-3.5464781487e+00, -3.4887717228e+00, 2.1520073481e+00],
+    ),
+];
+
+impl Body {
+    /// Sun and eight planets.
+    pub fn planets() -> Vec<Body> {
+        return vec![
+            Body::Sun,
+            Body::Mercury,
+            Body::Venus,
+            Body::Earth,
+            Body::Mars,
+            Body::Jupiter,
+            Body::Saturn,
+            Body::Uranus,
+            Body::Neptune,
+        ];
+    }
+
+    /// The Moon, four Galilean satellites of Jupiter and Titan.
+    pub fn satellites() -> Vec<Body> {
+        return vec![
+            Body::Moon,
+            Body::Io,
+            Body::Europa,
+            Body::Ganymede,
+            Body::Callisto,
+            Body::Titan,
+        ];
+    }
+
+    /// Planet of the satellite; `None` for the Sun and the planets.
+    pub fn planet(&self) -> Option<Body> {
+        return SATELLITES.iter().find(|row| row.0 == *self).map(|row| row.1);
+    }
+
+    /// Gravitational parameter in km^3 / s^2; planets include their satellites.
+    fn gm(&self) -> f64 {
+        return match self {
+            Body::Sun => GM_SUN,
+            Body::Mercury => GM_MERCURY,
+            Body::Venus => GM_VENUS,
+            Body::Earth => GM_EARTH + GM_MOON,
+            Body::Moon => GM_MOON,
+            Body::Mars => GM_MARS,
+            Body::Jupiter => GM_JUPITER,
+            Body::Io => GM_IO,
+            Body::Europa => GM_EUROPA,
+            Body::Ganymede => GM_GANYMEDE,
+            Body::Callisto => GM_CALLISTO,
+            Body::Saturn => GM_SATURN,
+            Body::Titan => GM_TITAN,
+            Body::Uranus => GM_URANUS,
+            Body::Neptune => GM_NEPTUNE,
+            Body::Pluto => GM_PLUTO,
+        };
+    }
+
+    /// Heliocentric position and velocity of the planet or planetocentric ones of the satellite from the tables.
+    fn state(&self) -> (Vector3, Vector3) {
+        let planet = PLANETS.iter().find(|row| row.0 == *self).map(|row| (row.1, row.2));
+        let satellite = SATELLITES.iter().find(|row| row.0 == *self).map(|row| (row.2, row.3));
+
+        return match planet.or(satellite) {
+            Some((r, v)) => (Vector3::new(r[0], r[1], r[2]), Vector3::new(v[0], v[1], v[2])),
+            None => (Vector3::null_vector(), Vector3::null_vector()),
+        };
+    }
+}
+
+/// Struct that handles creation of the Solar System at epoch J2000.0 from embedded state vectors.
+///
+/// Particles are created in the order of the list of bodies; masses are chosen so that `G m`
+/// is equal to the tabulated gravitational parameter with `G = Units::G`.
+pub struct SolarSystem {
+    bodies: Vec<Body>,
+    barycentric: bool,
+}
+
+impl SolarSystem {
+    /// Creates new `SolarSystem` struct with the Sun and eight planets in the heliocentric frame.
+    pub fn new() -> SolarSystem {
+        return SolarSystem {
+            bodies: Body::planets(),
+            barycentric: false,
+        };
+    }
+
+    /// Sets the list of bodies to generate; it must be non-empty, contain each body once,
+    /// and contain the planet of each included satellite.
+    pub fn set_bodies(&mut self, bodies: &[Body]) -> Result<(), Error> {
+        let empty_check = |bodies: &[Body]| -> bool { !bodies.is_empty() };
+        let unique_check =
+            |bodies: &[Body]| -> bool { (0..bodies.len()).all(|i| !bodies[..i].contains(&bodies[i])) };
+        let planet_check = |bodies: &[Body]| -> bool {
+            bodies.iter().all(|body| match body.planet() {
+                Some(planet) => bodies.contains(&planet),
+                None => true,
+            })
+        };
+
+        if !empty_check(bodies) {
+            return Err(Error::EmptySet);
+        }
+        if !unique_check(bodies) {
+            return Err(Error::invalid("bodies", format!("{:?}", bodies), "must not repeat"));
+        }
+        if !planet_check(bodies) {
+            return Err(Error::invalid("bodies", format!("{:?}", bodies), "satellites require their planets"));
+        }
+
+        self.bodies = bodies.to_vec();
+
+        return Ok(());
+    }
+
+    pub fn get_bodies(&self) -> &[Body] {
+        return &self.bodies;
+    }
+
+    /// If `barycentric` is true, the generated set is moved so that its centre of mass is at rest at the origin;
+    /// otherwise the Sun is at rest at the origin.
+    pub fn set_barycentric(&mut self, barycentric: bool) {
+        self.barycentric = barycentric;
+    }
+
+    pub fn get_barycentric(&self) -> bool {
+        return self.barycentric;
+    }
+
+    fn mass(gm: f64) -> ScalarQuantity {
+        return gm * 1e9 * Units::m.pow(3.) * Units::s.pow(-2.) / Units::G.convert();
+    }
+
+    /// Heliocentric position, velocity and gravitational parameter of the planet without its included satellites:
+    /// the planet and the satellites together are at the tabulated barycentre of its system.
+    fn planet_state(&self, planet: Body) -> (Vector3, Vector3, f64) {
+        let (mut r, mut v) = planet.state();
+        let mut gm = planet.gm();
+
+        for satellite in self.bodies.iter().filter(|body| body.planet() == Some(planet)) {
+            let (satellite_r, satellite_v) = satellite.state();
+            let fraction = satellite.gm() / planet.gm();
+
+            r -= satellite_r * fraction;
+            v -= satellite_v * fraction;
+            gm -= satellite.gm();
+        }
+
+        return (r, v, gm);
+    }
+}
+
+impl Generator for SolarSystem {
+    fn generate_with_rng(&self, _rng: &mut dyn RngCore) -> Result<ParticleSet, Error> {
+        let mut output = ParticleSet::new()?;
+
+        for body in self.bodies.iter() {
+            let (r, v, gm) = match body.planet() {
+                Some(planet) => {
+                    let (planet_r, planet_v, _) = self.planet_state(planet);
+                    let (r, v) = body.state();
+
+                    (planet_r + r, planet_v + v, body.gm())
+                }
+                None => self.planet_state(*body),
+            };
+
+            output.add_particle(Particle::new(r * 1e3 * Units::m, v * Units::kms, Self::mass(gm))?);
+        }
+
+        if self.barycentric {
+            output.move_to_center_of_mass()?;
+        }
+
+        return Ok(output);
+    }
+}
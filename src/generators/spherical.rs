@@ -0,0 +1,187 @@
+use super::eddington::TabulatedModel;
+use super::Generator;
+use crate::error::Error;
+use crate::particles::*;
+use crate::quantity::typed::{IntoTyped, Length, LengthDimension, Mass, MassDimension};
+use crate::quantity::Units;
+use rand::RngCore;
+
+/// Nodes of the density are placed `NODES_PER_DECADE` per decade of radius.
+const NODES_PER_DECADE: f64 = 100.;
+/// Default radial range of the nodes in the units of the scale radius.
+const DEFAULT_INNER_RADIUS: f64 = 1e-6;
+const DEFAULT_OUTER_RADIUS: f64 = 1e+6;
+
+/// Struct that handles creation of spherical system with arbitrary density profile `ρ(r)`.
+///
+/// The density is tabulated on logarithmic grid of radii and extended as power law inside and beyond it;
+/// the mass profile and the potential are integrated numerically and the distribution function is computed
+/// by Eddington inversion. The system may be placed into an external spherical potential (e.g. of a central black hole
+/// or of another component), which changes velocities but not positions.
+///
+/// By default the velocities are isotropic; with [Osipkov-Merritt](https://doi.org/10.1086/113540) anisotropy
+/// the distribution function depends on `Q = Ψ - v^2 / 2 - L^2 / (2 r_a^2)` and the anisotropy parameter is
+/// `β(r) = r^2 / (r^2 + r_a^2)`: orbits are isotropic inside the anisotropy radius `r_a` and radial outside it.
+#[allow(non_snake_case)]
+pub struct Spherical {
+    density: Box<dyn Fn(f64) -> f64>,
+    external_potential: Option<Box<dyn Fn(f64) -> f64>>,
+    scale_radius: f64,
+    inner_radius: f64,
+    outer_radius: f64,
+    anisotropy_radius: f64,
+    n: usize,
+    m0: f64,
+    G: f64,
+}
+
+impl Spherical {
+    /// Creates new `Spherical` struct with given parameters:
+    /// `density` - density as a function of radius in the units of the scale radius; its normalisation is arbitrary,
+    /// `r` - scale radius,
+    /// `n` - number of particles,
+    /// `m` - whole mass of the system.
+    ///
+    /// `r` and `m` are either typed `Length` and `Mass` or dynamic quantities equivalent to `Units::m` and `Units::kg`.
+    /// The density must be positive inside the radial range (from `10^-6` to `10^6` scale radii by default,
+    /// see `set_radial_range`), shallower than `r^-3` at its inner edge and steeper than `r^-3` at the outer one.
+    pub fn new(
+        density: impl Fn(f64) -> f64 + 'static,
+        r: impl IntoTyped<f64, LengthDimension>,
+        n: usize,
+        m: impl IntoTyped<f64, MassDimension>,
+    ) -> Result<Spherical, Error> {
+        let rad_check = |rad: &Length| -> bool { *rad > Length::from_si(0.) && rad.value_si().is_finite() };
+        let n_check = |number: usize| -> bool { number != 0 };
+        let m_check = |mass: &Mass| -> bool { *mass > Mass::from_si(0.) && mass.value_si().is_finite() };
+
+        let r = r.into_typed("radius")?;
+        let m = m.into_typed("mass")?;
+
+        if !rad_check(&r) {
+            return Err(Error::invalid("radius", r, "must be positive and finite"));
+        }
+        if !n_check(n) {
+            return Err(Error::invalid("number of particles", n, "must be positive"));
+        }
+        if !m_check(&m) {
+            return Err(Error::invalid("mass", m, "must be positive and finite"));
+        }
+
+        return Ok(Spherical {
+            density: Box::new(density),
+            external_potential: None,
+            scale_radius: r.value_si(),
+            inner_radius: DEFAULT_INNER_RADIUS,
+            outer_radius: DEFAULT_OUTER_RADIUS,
+            anisotropy_radius: f64::INFINITY,
+            n,
+            m0: m.value_si(),
+            G: Units::G
+                .convert()
+                .value_in_q(Units::m.pow(3.) * Units::kg.pow(-1.) * Units::s.pow(-2.)),
+        });
+    }
+
+    /// Sets the radii of the innermost and the outermost nodes of the density in the units of the scale radius.
+    pub fn set_radial_range(&mut self, inner: f64, outer: f64) -> Result<(), Error> {
+        let inner_check = |inner: f64| -> bool { inner > 0. };
+        let outer_check = |inner: f64, outer: f64| -> bool { outer > inner && outer.is_finite() };
+
+        if !inner_check(inner) {
+            return Err(Error::invalid("inner radius", inner, "must be positive"));
+        }
+        if !outer_check(inner, outer) {
+            return Err(Error::invalid("outer radius", outer, "must be finite and greater than the inner one"));
+        }
+
+        self.inner_radius = inner;
+        self.outer_radius = outer;
+
+        return Ok(());
+    }
+
+    pub fn get_radial_range(&self) -> (f64, f64) {
+        return (self.inner_radius, self.outer_radius);
+    }
+
+    /// Sets the external relative potential `Ψ = -Φ` as a function of radius; both are in the units of the model:
+    /// radius in the units of the scale radius and potential in the units of `G m / r`, so a central point mass
+    /// `μ m` is `move |x| μ / x`. The potential must be positive and decrease to zero at infinity.
+    pub fn set_external_potential(&mut self, potential: impl Fn(f64) -> f64 + 'static) {
+        self.external_potential = Some(Box::new(potential));
+    }
+
+    /// Sets Osipkov-Merritt anisotropy radius; it must be positive and may be infinite, which means isotropic velocities.
+    pub fn set_anisotropy_radius(&mut self, r: impl IntoTyped<f64, LengthDimension>) -> Result<(), Error> {
+        let rad_check = |rad: &Length| -> bool { *rad > Length::from_si(0.) };
+
+        let r = r.into_typed("anisotropy radius")?;
+
+        if !rad_check(&r) {
+            return Err(Error::invalid("anisotropy radius", r, "must be positive"));
+        }
+
+        self.anisotropy_radius = r.value_si();
+
+        return Ok(());
+    }
+
+    pub fn get_anisotropy_radius(&self) -> Length {
+        return Length::from_si(self.anisotropy_radius);
+    }
+
+    pub fn get_scale_radius(&self) -> Length {
+        return Length::from_si(self.scale_radius);
+    }
+
+    /// Tabulated model in the units of the scale radius and of the normalisation of the density.
+    fn model(&self) -> Result<TabulatedModel<'_>, Error> {
+        let start = self.inner_radius.log10();
+        let nodes = ((self.outer_radius.log10() - start) * NODES_PER_DECADE).ceil() as usize;
+
+        let radii: Vec<f64> = (0..=nodes)
+            .map(|i| 10_f64.powf(start + i as f64 / NODES_PER_DECADE))
+            .collect();
+        let densities: Vec<f64> = radii.iter().map(|r| (self.density)(*r)).collect();
+        let density_check = |density: f64| -> bool { density > 0. && density.is_finite() };
+
+        for i in 0..=nodes {
+            if !density_check(densities[i]) {
+                return Err(Error::invalid(
+                    "density",
+                    format!("{} at radius {}", densities[i], radii[i]),
+                    "must be positive and finite inside the radial range",
+                ));
+            }
+        }
+
+        return TabulatedModel::new(
+            &radii,
+            &densities,
+            self.external_potential.as_deref(),
+            self.anisotropy_radius / self.scale_radius,
+        );
+    }
+}
+
+impl Generator for Spherical {
+    fn generate_with_rng(&self, rng: &mut dyn RngCore) -> Result<ParticleSet, Error> {
+        let model = self.model()?;
+        let velocity_scale = (self.G * self.m0 / (self.scale_radius * model.total_mass())).sqrt();
+        let mass = self.m0 / (self.n as f64);
+        let mut output = ParticleSet::new()?;
+
+        for _ in 0..self.n {
+            let (position, velocity) = model.sample(rng);
+
+            output.add_particle(Particle::new(
+                position * self.scale_radius * Units::m,
+                velocity * velocity_scale * Units::ms,
+                mass * Units::kg,
+            )?);
+        }
+
+        return Ok(output);
+    }
+}
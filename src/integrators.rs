@@ -1,19 +1,39 @@
+use crate::error::Error;
 use crate::particles::ParticleSet;
 use crate::quantity::ScalarQuantity;
 
 /// Trait that is used to handle structures that are responsible for numerical integration of `ParticleSet`
 pub trait Integrator {
     /// This method returns state of the system after the last `evolve` call (or just initialisation of integrator).
+    /// Particles keep identifiers of the ones that integrator was initialised with.
     /// 
-    /// `return`: ParticleSet with particles representing current state or the error message 
-    fn get_state(&self) -> Result<ParticleSet, &'static str>;
+    /// `return`: ParticleSet with particles representing current state or the error 
+    fn get_state(&self) -> Result<ParticleSet, Error>;
     /// Integrates the whole system up to `time`.
     /// 
     /// ## Arguments
     /// 
     /// * `time`: a `ScalarQuantity` equivalent to seconds  
-    fn evolve(&mut self, time: &ScalarQuantity) -> Result<(), String>;
+    fn evolve(&mut self, time: &ScalarQuantity) -> Result<(), Error>;
 }
 
+/// This module contains integrator that uses Barnes-Hut octree for force calculation.
+pub mod barnes_hut;
+/// This module contains integrator with individual power-of-two timesteps of particles.
+pub mod block_timestep;
+/// This module contains direct summation of gravitational interaction over pairs of particles.
+pub(crate) mod direct;
+/// This module contains fourth-order Hermite predictor-corrector integrator.
+pub mod hermite;
+/// This module contains 15th-order Gauss-Radau integrator with adaptive timestep (IAS15).
+pub mod ias15;
+/// This module contains methods that advance the state of the system by one timestep.
+pub mod scheme;
 /// This module contains the simplest implementation of `Integrator` trait.
 pub mod simple_nbody;
+/// This module contains the state of particles and the steps of evolution that are shared by integrators.
+pub(crate) mod state;
+/// This module contains criteria of the adaptive choice of the timestep.
+pub mod timestep;
+/// This module contains Wisdom-Holman symplectic map for systems with one dominant body.
+pub mod wisdom_holman;
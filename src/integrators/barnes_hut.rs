@@ -0,0 +1,392 @@
+use super::scheme::Scheme;
+use super::state::{fixed_steps, SystemState};
+use super::Integrator;
+use crate::error::Error;
+use crate::particles::ParticleSet;
+use crate::quantity::typed::{IntoTyped, TimeDimension};
+use crate::quantity::systems::{SIUnits, UnitSystem};
+use crate::quantity::{ScalarQuantity, VectorQuantity};
+use crate::softening::Softening;
+use crate::vector::Vector3;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Maximal depth of the octree; particles that are still not separated on this depth share one leaf.
+const MAX_DEPTH: usize = 64;
+
+/// Node of the octree: cube with multipole moments of all particles inside it.
+struct Node {
+    center: Vector3,
+    half_size: f64,
+    mass: f64,
+    center_of_mass: Vector3,
+    /// Traceless quadrupole moment about the center of mass: `xx, yy, zz, xy, xz, yz` components.
+    quadrupole: [f64; 6],
+    children: Vec<usize>,
+    bodies: Vec<usize>,
+}
+
+impl Node {
+    fn contains(&self, pos: Vector3) -> bool {
+        return (pos.x - self.center.x).abs() <= self.half_size
+            && (pos.y - self.center.y).abs() <= self.half_size
+            && (pos.z - self.center.z).abs() <= self.half_size;
+    }
+
+    /// Product of quadrupole tensor and vector `x`.
+    fn quadrupole_dot(&self, x: Vector3) -> Vector3 {
+        let q = &self.quadrupole;
+
+        return Vector3::new(
+            q[0] * x.x + q[3] * x.y + q[4] * x.z,
+            q[3] * x.x + q[1] * x.y + q[5] * x.z,
+            q[4] * x.x + q[5] * x.y + q[2] * x.z,
+        );
+    }
+}
+
+/// Quadrupole moment `m * (3 * d * d - d^2 * I)` of the point mass `m` at the displacement `d`.
+fn point_quadrupole(m: f64, d: Vector3) -> [f64; 6] {
+    let d2 = d.dot(&d);
+
+    return [
+        m * (3. * d.x * d.x - d2),
+        m * (3. * d.y * d.y - d2),
+        m * (3. * d.z * d.z - d2),
+        m * 3. * d.x * d.y,
+        m * 3. * d.x * d.z,
+        m * 3. * d.y * d.z,
+    ];
+}
+
+/// Octree over the positions of particles; node `0` is the root.
+struct Octree {
+    nodes: Vec<Node>,
+}
+
+impl Octree {
+    fn new(positions: &[Vector3], masses: &[f64]) -> Octree {
+        let mut tree = Octree { nodes: Vec::new() };
+
+        if positions.is_empty() {
+            return tree;
+        }
+
+        let mut min = positions[0];
+        let mut max = positions[0];
+
+        for p in positions.iter() {
+            min = Vector3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z));
+            max = Vector3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z));
+        }
+
+        let center = (min + max) / 2.;
+        let size = max - min;
+        let half_size = size.x.max(size.y).max(size.z) / 2. * 1.0001;
+
+        tree.build(
+            (0..positions.len()).collect(),
+            center,
+            half_size,
+            0,
+            positions,
+            masses,
+        );
+
+        return tree;
+    }
+
+    /// Recursively creates node containing `bodies` and returns its index.
+    fn build(
+        &mut self,
+        bodies: Vec<usize>,
+        center: Vector3,
+        half_size: f64,
+        depth: usize,
+        positions: &[Vector3],
+        masses: &[f64],
+    ) -> usize {
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            center,
+            half_size,
+            mass: 0.,
+            center_of_mass: center,
+            quadrupole: [0.; 6],
+            children: Vec::new(),
+            bodies: Vec::new(),
+        });
+
+        let mut mass = 0.;
+        let mut weighted = Vector3::null_vector();
+
+        for &i in bodies.iter() {
+            mass += masses[i];
+            weighted += masses[i] * positions[i];
+        }
+
+        let center_of_mass = if mass > 0. {
+            weighted / mass
+        } else {
+            center
+        };
+        let mut quadrupole = [0.; 6];
+
+        if bodies.len() <= 1 || depth >= MAX_DEPTH {
+            for &i in bodies.iter() {
+                let q = point_quadrupole(masses[i], positions[i] - center_of_mass);
+
+                for k in 0..6 {
+                    quadrupole[k] += q[k];
+                }
+            }
+
+            self.nodes[index].bodies = bodies;
+        } else {
+            let mut octants: Vec<Vec<usize>> = vec![Vec::new(); 8];
+
+            for &i in bodies.iter() {
+                let p = positions[i];
+                let octant = (p.x > center.x) as usize
+                    + 2 * (p.y > center.y) as usize
+                    + 4 * (p.z > center.z) as usize;
+                octants[octant].push(i);
+            }
+
+            let quarter = half_size / 2.;
+            let mut children = Vec::new();
+
+            for (octant, subset) in octants.into_iter().enumerate() {
+                if subset.is_empty() {
+                    continue;
+                }
+
+                let sign = |bit: usize| if octant & bit != 0 { 1. } else { -1. };
+                let child_center = center
+                    + Vector3::new(sign(1) * quarter, sign(2) * quarter, sign(4) * quarter);
+                let child = self.build(subset, child_center, quarter, depth + 1, positions, masses);
+                let node = &self.nodes[child];
+                let shift = point_quadrupole(node.mass, node.center_of_mass - center_of_mass);
+
+                for k in 0..6 {
+                    quadrupole[k] += node.quadrupole[k] + shift[k];
+                }
+
+                children.push(child);
+            }
+
+            self.nodes[index].children = children;
+        }
+
+        let node = &mut self.nodes[index];
+        node.mass = mass;
+        node.center_of_mass = center_of_mass;
+        node.quadrupole = quadrupole;
+
+        return index;
+    }
+}
+
+/// Integrator that uses Barnes-Hut tree algorithm for force calculation and one of `Scheme` methods for numerical integration.
+pub struct BarnesHut {
+    state: SystemState,
+    timestep: f64,
+    scheme: Scheme,
+    accelerations: Option<Vec<Vector3>>,
+    theta: f64,
+    quadrupole: bool,
+}
+
+impl BarnesHut {
+    /// Initialises integrator that uses semi-implicit Euler method.
+    pub fn new(particle_set: &ParticleSet) -> Result<BarnesHut, Error> {
+        return Self::with_scheme(particle_set, Scheme::Euler);
+    }
+
+    /// Initialises integrator that uses given integration scheme.
+    pub fn with_scheme(particle_set: &ParticleSet, scheme: Scheme) -> Result<BarnesHut, Error> {
+        return Self::with_units(particle_set, scheme, SIUnits);
+    }
+
+    /// Initialises integrator that uses given integration scheme and stores the state of the system in given units.
+    /// Units close to the scales of the system (for example, `NBodyUnits`) reduce round-off errors;
+    /// all the quantities are still passed and returned in physical units.
+    pub fn with_units(
+        particle_set: &ParticleSet,
+        scheme: Scheme,
+        units: impl UnitSystem + 'static,
+    ) -> Result<BarnesHut, Error> {
+        return Ok(BarnesHut {
+            state: SystemState::new(particle_set, units),
+            timestep: 1.0,
+            scheme,
+            accelerations: None,
+            theta: 0.5,
+            quadrupole: false,
+        });
+    }
+
+    /// Sets timestep; `dt` is either typed `Time` or dynamic quantity equivalent to `Units::s`.
+    pub fn set_timestep(&mut self, dt: impl IntoTyped<f64, TimeDimension>) -> Result<(), Error> {
+        self.timestep = self.state.timestep(dt)?;
+
+        return Ok(());
+    }
+
+    pub fn get_timestep(&self) -> ScalarQuantity {
+        return self.timestep * self.state.units.time();
+    }
+
+    /// Sets softening of the gravitational interaction between particles.
+    pub fn set_softening(&mut self, softening: Softening) {
+        self.state.set_softening(softening);
+        self.accelerations = None;
+    }
+
+    pub fn get_softening(&self) -> Softening {
+        return self.state.get_softening();
+    }
+
+    pub fn get_scheme(&self) -> Scheme {
+        return self.scheme;
+    }
+
+    /// Sets opening angle: node of size `s` at the distance `d` is treated as a single body if `s / d < theta`.
+    /// `theta = 0` gives exact direct summation; default value is `0.5`.
+    pub fn set_opening_angle(&mut self, theta: f64) -> Result<(), Error> {
+        if theta.is_nan() || theta < 0. {
+            return Err(Error::invalid("opening angle", theta, "must be non-negative"));
+        }
+
+        self.theta = theta;
+        self.accelerations = None;
+
+        return Ok(());
+    }
+
+    pub fn get_opening_angle(&self) -> f64 {
+        return self.theta;
+    }
+
+    /// Enables or disables quadrupole correction to the monopole approximation of distant nodes.
+    pub fn set_quadrupole(&mut self, quadrupole: bool) {
+        self.quadrupole = quadrupole;
+        self.accelerations = None;
+    }
+
+    pub fn get_quadrupole(&self) -> bool {
+        return self.quadrupole;
+    }
+
+    /// Returns accelerations of particles in the current state of the system.
+    ///
+    /// `return`: `VectorQuantity` equivalent to `Units::ms / Units::s` for each particle
+    pub fn get_accelerations(&self) -> Vec<VectorQuantity> {
+        return self
+            .compute_accelerations(&self.state.positions)
+            .into_iter()
+            .map(|a| a * self.state.units.acceleration())
+            .collect();
+    }
+
+    fn integrate(&mut self, dt: f64) {
+        let scheme = self.scheme;
+        let mut positions = std::mem::take(&mut self.state.positions);
+        let mut velocities = std::mem::take(&mut self.state.velocities);
+        let mut accelerations = self.accelerations.take();
+
+        scheme.step(
+            &mut positions,
+            &mut velocities,
+            &mut accelerations,
+            dt,
+            |pos| self.compute_accelerations(pos),
+        );
+
+        self.state.positions = positions;
+        self.state.velocities = velocities;
+        self.accelerations = accelerations;
+        self.state.model_time += dt;
+    }
+
+    fn compute_accelerations(&self, positions: &[Vector3]) -> Vec<Vector3> {
+        let tree = Octree::new(positions, &self.state.masses);
+
+        #[cfg(feature = "parallel")]
+        let iter = (0..positions.len()).into_par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let iter = 0..positions.len();
+
+        return iter
+            .map(|i| self.get_acceleration_from_node(&tree, 0, positions, i))
+            .collect();
+    }
+
+    fn get_acceleration_from_node(
+        &self,
+        tree: &Octree,
+        index: usize,
+        positions: &[Vector3],
+        i: usize,
+    ) -> Vector3 {
+        let node = &tree.nodes[index];
+        let pos = positions[i];
+        let mut result = Vector3::null_vector();
+
+        if node.children.is_empty() {
+            for &j in node.bodies.iter() {
+                if j == i {
+                    continue;
+                }
+
+                let dist = positions[j] - pos;
+                result += self.state.G
+                    * self.state.masses[j]
+                    * self.state.kernel.force_factor(dist.dot(&dist), self.state.eps)
+                    * dist;
+            }
+
+            return result;
+        }
+
+        let dist = node.center_of_mass - pos;
+        let r2 = dist.dot(&dist);
+        let size = 2. * node.half_size;
+
+        if node.contains(pos) || size * size >= self.theta * self.theta * r2 {
+            for &child in node.children.iter() {
+                result += self.get_acceleration_from_node(tree, child, positions, i);
+            }
+
+            return result;
+        }
+
+        result += self.state.G * node.mass * self.state.kernel.force_factor(r2, self.state.eps) * dist;
+
+        if self.quadrupole {
+            let x = pos - node.center_of_mass;
+            let qx = node.quadrupole_dot(x);
+            let r5 = r2 * r2 * r2.sqrt();
+
+            result += self.state.G * (qx / r5 - 2.5 * x.dot(&qx) / (r5 * r2) * x);
+        }
+
+        return result;
+    }
+}
+
+impl Integrator for BarnesHut {
+    fn get_state(&self) -> Result<ParticleSet, Error> {
+        return self.state.get_state();
+    }
+
+    fn evolve(&mut self, time: &ScalarQuantity) -> Result<(), Error> {
+        let time = self.state.end_time(time)?;
+
+        for dt in fixed_steps(self.state.model_time, time, self.timestep) {
+            self.integrate(dt);
+        }
+
+        return Ok(());
+    }
+}
@@ -0,0 +1,252 @@
+use super::state::SystemState;
+use super::timestep::{defined_timescale, AdaptiveTimestep, TimestepCriterion};
+use super::Integrator;
+use crate::error::Error;
+use crate::particles::ParticleSet;
+use crate::quantity::systems::{SIUnits, UnitSystem};
+use crate::quantity::ScalarQuantity;
+use crate::softening::Softening;
+use crate::vector::Vector3;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Deepest level of the hierarchy: timestep on it is `2^-MAX_LEVEL` of the largest one,
+/// so the maximal timestep may exceed the minimal one at most `2^MAX_LEVEL` times.
+const MAX_LEVEL: u32 = 40;
+
+/// Integrator with individual timesteps of particles quantized to the power-of-two hierarchy.
+///
+/// Interval of each `evolve` call is split into equal largest steps `dt` that do not exceed maximal timestep.
+/// Particle on the level `k` has timestep `dt / 2^k`, which is the largest one of this form that does not exceed
+/// the timestep given by the criterion. Each particle is advanced by kick-drift-kick leapfrog: all particles
+/// are drifted together, but only the ones whose step ends are kicked with newly computed accelerations.
+/// A particle may move to the coarser level only when its step is synchronized with that level,
+/// so all particles reach the end of `dt` (and the end time of `evolve`) at once.
+pub struct BlockTimestep {
+    state: SystemState,
+    timestep: AdaptiveTimestep,
+    accelerations: Vec<Vector3>,
+    timescales: Vec<f64>,
+    synchronized: bool,
+    levels: Vec<u32>,
+}
+
+impl BlockTimestep {
+    /// Initialises integrator that chooses timesteps of particles by `timestep`.
+    pub fn new(particle_set: &ParticleSet, timestep: AdaptiveTimestep) -> Result<BlockTimestep, Error> {
+        return Self::with_units(particle_set, timestep, SIUnits);
+    }
+
+    /// Initialises integrator that chooses timesteps of particles by `timestep` and stores the state of the system in given units.
+    pub fn with_units(
+        particle_set: &ParticleSet,
+        timestep: AdaptiveTimestep,
+        units: impl UnitSystem + 'static,
+    ) -> Result<BlockTimestep, Error> {
+        let n = particle_set.particles.len();
+
+        Self::check_levels(&timestep)?;
+
+        return Ok(BlockTimestep {
+            state: SystemState::new(particle_set, units),
+            timestep,
+            accelerations: vec![Vector3::null_vector(); n],
+            timescales: vec![0.; n],
+            synchronized: false,
+            levels: vec![0; n],
+        });
+    }
+
+    /// Sets parameters of the choice of timesteps; the ratio of the maximal and the minimal timestep
+    /// must not exceed `2^40`, the number of levels of the hierarchy.
+    pub fn set_timestep(&mut self, timestep: AdaptiveTimestep) -> Result<(), Error> {
+        Self::check_levels(&timestep)?;
+
+        self.timestep = timestep;
+        self.synchronized = false;
+
+        return Ok(());
+    }
+
+    pub fn get_timestep(&self) -> AdaptiveTimestep {
+        return self.timestep;
+    }
+
+    /// Sets softening of the gravitational interaction between particles.
+    pub fn set_softening(&mut self, softening: Softening) {
+        self.state.set_softening(softening);
+        self.synchronized = false;
+    }
+
+    pub fn get_softening(&self) -> Softening {
+        return self.state.get_softening();
+    }
+
+    /// Returns number of particles on each level at the end of the last `evolve` call;
+    /// element `k` corresponds to timestep `dt / 2^k`, where `dt` is the largest step of that call.
+    pub fn get_level_statistics(&self) -> Vec<usize> {
+        let deepest = self.levels.iter().max().map_or(0, |l| *l as usize);
+        let mut result = vec![0; deepest + 1];
+
+        for level in self.levels.iter() {
+            result[*level as usize] += 1;
+        }
+
+        return result;
+    }
+
+    /// Advances all particles by the largest step `dt`.
+    fn block_step(&mut self, dt: f64) {
+        let n = self.state.positions.len();
+        let end: u64 = 1 << MAX_LEVEL;
+        let tick = dt / end as f64;
+        let ticks = |level: u32| -> u64 { 1 << (MAX_LEVEL - level) };
+        let mut starts = vec![0_u64; n];
+
+        for i in 0..n {
+            self.levels[i] = self.level(self.timescales[i], dt);
+            self.state.velocities[i] += self.accelerations[i] * (ticks(self.levels[i]) as f64 * tick / 2.);
+        }
+
+        let mut time: u64 = 0;
+
+        while time < end {
+            let next = (0..n).map(|i| starts[i] + ticks(self.levels[i])).min().unwrap_or(end);
+
+            for i in 0..n {
+                self.state.positions[i] += self.state.velocities[i] * ((next - time) as f64 * tick);
+            }
+
+            time = next;
+
+            let active: Vec<usize> = (0..n)
+                .filter(|i| starts[*i] + ticks(self.levels[*i]) == time)
+                .collect();
+            self.update(&active);
+
+            for i in active {
+                self.state.velocities[i] += self.accelerations[i] * (ticks(self.levels[i]) as f64 * tick / 2.);
+
+                if time == end {
+                    continue;
+                }
+
+                let mut level = self.level(self.timescales[i], dt);
+
+                // the step of the level must start at its multiple; `ticks` is a power of two
+                while time & (ticks(level) - 1) != 0 {
+                    level += 1;
+                }
+
+                self.levels[i] = level;
+                starts[i] = time;
+                self.state.velocities[i] += self.accelerations[i] * (ticks(level) as f64 * tick / 2.);
+            }
+        }
+    }
+
+    /// Returns the level of the particle with given timescale for the largest step `dt`.
+    /// Returns error if the deepest level can not reach the minimal timestep of `timestep`.
+    fn check_levels(timestep: &AdaptiveTimestep) -> Result<(), Error> {
+        let levels_check = |ratio: f64| -> bool { ratio <= 2_f64.powi(MAX_LEVEL as i32) };
+
+        if !levels_check(timestep.get_max().value_si() / timestep.get_min().value_si()) {
+            return Err(Error::invalid(
+                "maximal timestep",
+                timestep.get_max(),
+                "must not exceed 2^40 minimal timesteps",
+            ));
+        }
+
+        return Ok(());
+    }
+
+    fn level(&self, timescale: f64, dt: f64) -> u32 {
+        let step = self.timestep.timestep(timescale, self.state.units.as_ref());
+        let level = (dt / step).log2().ceil();
+
+        if level <= 0. {
+            return 0;
+        }
+
+        return (level as u32).min(MAX_LEVEL);
+    }
+
+    /// Computes accelerations and timescales of `active` particles at current positions.
+    fn update(&mut self, active: &[usize]) {
+        let sum = self.state.direct_sum();
+        let criterion = self.timestep.get_criterion();
+        let (positions, velocities, eps) = (&self.state.positions, &self.state.velocities, self.state.eps);
+        let evaluate = |i: &usize| -> (Vector3, f64) {
+            match criterion {
+                TimestepCriterion::Acceleration => {
+                    let acc = sum.acceleration_of(*i, positions);
+
+                    (acc, (eps / acc.mag()).sqrt())
+                }
+                TimestepCriterion::Aarseth => {
+                    let (acc, jerk) = sum.acceleration_and_jerk_of(*i, positions, velocities);
+
+                    (acc, defined_timescale(acc.mag() / jerk.mag()))
+                }
+                TimestepCriterion::FreeFall => (
+                    sum.acceleration_of(*i, positions),
+                    sum.free_fall_time_of(*i, positions),
+                ),
+            }
+        };
+
+        #[cfg(feature = "parallel")]
+        let results: Vec<(Vector3, f64)> = active.par_iter().map(evaluate).collect();
+        #[cfg(not(feature = "parallel"))]
+        let results: Vec<(Vector3, f64)> = active.iter().map(evaluate).collect();
+
+        for k in 0..active.len() {
+            self.accelerations[active[k]] = results[k].0;
+            self.timescales[active[k]] = results[k].1;
+        }
+    }
+}
+
+impl Integrator for BlockTimestep {
+    fn get_state(&self) -> Result<ParticleSet, Error> {
+        return self.state.get_state();
+    }
+
+    fn evolve(&mut self, time: &ScalarQuantity) -> Result<(), Error> {
+        let time_check = |t: f64| -> bool { t >= self.state.model_time };
+        let end = self.state.end_time(time)?;
+
+        if !time_check(end) {
+            return Err(Error::invalid("time", time, "must not be less than model time"));
+        }
+
+        self.timestep.check_softening(self.state.eps)?;
+        self.state.direct_sum().check_coincident(&self.state.positions, &self.state.ids)?;
+
+        if end == self.state.model_time {
+            return Ok(());
+        }
+
+        if !self.synchronized {
+            let all: Vec<usize> = (0..self.state.positions.len()).collect();
+            self.update(&all);
+            self.synchronized = true;
+        }
+
+        let max = self.timestep.get_max().get_quantity().value_in_system(self.state.units.as_ref());
+        let start = self.state.model_time;
+        let steps = ((end - start) / max).ceil().max(1.);
+        let dt = (end - start) / steps;
+
+        for k in 1..=(steps as u64) {
+            self.block_step(dt);
+            self.state.model_time = start + k as f64 * dt;
+        }
+
+        // the sum may differ from `end` by round-off error
+        self.state.model_time = end;
+
+        return Ok(());
+    }
+}
@@ -0,0 +1,253 @@
+use crate::error::Error;
+use crate::softening::SofteningKernel;
+use crate::vector::Vector3;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Number of blocks that pairs of particles are split into. It does not depend on the number of threads,
+/// so that the blocks are summed in the same order and the results are the same for any number of them.
+const BLOCKS: usize = 32;
+
+/// Direct summation of gravitational interaction of particles. Functions for the whole system visit each pair once,
+/// functions for one particle sum over all of its partners.
+#[allow(non_snake_case)]
+pub(crate) struct DirectSum<'a> {
+    pub masses: &'a [f64],
+    pub G: f64,
+    pub kernel: SofteningKernel,
+    pub eps: f64,
+}
+
+impl<'a> DirectSum<'a> {
+    /// Returns accelerations of particles at `positions`; each pair gives equal and opposite contributions
+    /// to momentum, so that the total momentum is conserved up to round-off errors.
+    pub fn accelerations(&self, positions: &[Vector3]) -> Vec<Vector3> {
+        let n = positions.len();
+        let block = |b: usize| -> Vec<Vector3> {
+            let mut result = vec![Vector3::null_vector(); n];
+
+            // rows are interleaved between blocks, so that they have approximately the same number of pairs
+            for i in (b..n).step_by(BLOCKS) {
+                for j in (i + 1)..n {
+                    let d = positions[j] - positions[i];
+                    let f = self.G * self.kernel.force_factor(d.dot(&d), self.eps) * d;
+
+                    result[i] += self.masses[j] * f;
+                    result[j] -= self.masses[i] * f;
+                }
+            }
+
+            return result;
+        };
+
+        let mut result = vec![Vector3::null_vector(); n];
+
+        for partial in self.blocks(n, block) {
+            for i in 0..n {
+                result[i] += partial[i];
+            }
+        }
+
+        return result;
+    }
+
+    /// Returns accelerations and their time derivatives (jerks) of particles at `positions` moving with `velocities`.
+    pub fn accelerations_and_jerks(
+        &self,
+        positions: &[Vector3],
+        velocities: &[Vector3],
+    ) -> (Vec<Vector3>, Vec<Vector3>) {
+        let n = positions.len();
+        let block = |b: usize| -> (Vec<Vector3>, Vec<Vector3>) {
+            let mut acc = vec![Vector3::null_vector(); n];
+            let mut jerk = vec![Vector3::null_vector(); n];
+
+            for i in (b..n).step_by(BLOCKS) {
+                for j in (i + 1)..n {
+                    let d = positions[j] - positions[i];
+                    let v = velocities[j] - velocities[i];
+                    let r2 = d.dot(&d);
+                    let g = self.kernel.force_factor(r2, self.eps);
+                    let f = self.G * g * d;
+                    let df = self.G * (g * v + self.kernel.jerk_factor(r2, self.eps) * d.dot(&v) * d);
+
+                    acc[i] += self.masses[j] * f;
+                    acc[j] -= self.masses[i] * f;
+                    jerk[i] += self.masses[j] * df;
+                    jerk[j] -= self.masses[i] * df;
+                }
+            }
+
+            return (acc, jerk);
+        };
+
+        let mut acc = vec![Vector3::null_vector(); n];
+        let mut jerk = vec![Vector3::null_vector(); n];
+
+        for (partial_acc, partial_jerk) in self.blocks(n, block) {
+            for i in 0..n {
+                acc[i] += partial_acc[i];
+                jerk[i] += partial_jerk[i];
+            }
+        }
+
+        return (acc, jerk);
+    }
+
+    /// Returns the shortest free-fall time `1 / sqrt(G * (m_i + m_j) * g(r))` over the partners of each particle,
+    /// where `g(r)` is the force factor of the kernel; for the Newtonian interaction it is `sqrt(r^3 / (G * (m_i + m_j)))`.
+    pub fn free_fall_times(&self, positions: &[Vector3]) -> Vec<f64> {
+        let n = positions.len();
+        let block = |b: usize| -> Vec<f64> {
+            let mut result = vec![f64::INFINITY; n];
+
+            for i in (b..n).step_by(BLOCKS) {
+                for j in (i + 1)..n {
+                    let d = positions[j] - positions[i];
+                    let g = self.kernel.force_factor(d.dot(&d), self.eps);
+                    let t = 1. / (self.G * (self.masses[i] + self.masses[j]) * g).sqrt();
+
+                    result[i] = result[i].min(t);
+                    result[j] = result[j].min(t);
+                }
+            }
+
+            return result;
+        };
+
+        let mut result = vec![f64::INFINITY; n];
+
+        for partial in self.blocks(n, block) {
+            for i in 0..n {
+                result[i] = result[i].min(partial[i]);
+            }
+        }
+
+        return result;
+    }
+
+    /// Returns potential energy of particles at `positions`.
+    pub fn potential_energy(&self, positions: &[Vector3]) -> f64 {
+        let n = positions.len();
+        let block = |b: usize| -> f64 {
+            let mut result = 0.;
+
+            for i in (b..n).step_by(BLOCKS) {
+                for j in (i + 1)..n {
+                    let d = positions[j] - positions[i];
+                    let p = self.kernel.potential_factor(d.dot(&d), self.eps);
+
+                    result -= self.G * self.masses[i] * self.masses[j] * p;
+                }
+            }
+
+            return result;
+        };
+
+        return self.blocks(n, block).into_iter().sum();
+    }
+
+    /// Returns acceleration of the particle `index`.
+    pub fn acceleration_of(&self, index: usize, positions: &[Vector3]) -> Vector3 {
+        let mut result = Vector3::null_vector();
+
+        for j in 0..positions.len() {
+            if j == index {
+                continue;
+            }
+
+            let d = positions[j] - positions[index];
+            result += self.G * self.masses[j] * self.kernel.force_factor(d.dot(&d), self.eps) * d;
+        }
+
+        return result;
+    }
+
+    /// Returns acceleration and jerk of the particle `index`.
+    pub fn acceleration_and_jerk_of(
+        &self,
+        index: usize,
+        positions: &[Vector3],
+        velocities: &[Vector3],
+    ) -> (Vector3, Vector3) {
+        let mut acc = Vector3::null_vector();
+        let mut jerk = Vector3::null_vector();
+
+        for j in 0..positions.len() {
+            if j == index {
+                continue;
+            }
+
+            let d = positions[j] - positions[index];
+            let v = velocities[j] - velocities[index];
+            let r2 = d.dot(&d);
+            let g = self.kernel.force_factor(r2, self.eps);
+
+            acc += self.G * self.masses[j] * g * d;
+            jerk += self.G * self.masses[j] * (g * v + self.kernel.jerk_factor(r2, self.eps) * d.dot(&v) * d);
+        }
+
+        return (acc, jerk);
+    }
+
+    /// Returns the shortest free-fall time of the particle `index` and one of its partners, see `free_fall_times`.
+    pub fn free_fall_time_of(&self, index: usize, positions: &[Vector3]) -> f64 {
+        let mut result = f64::INFINITY;
+
+        for j in 0..positions.len() {
+            if j == index {
+                continue;
+            }
+
+            let d = positions[j] - positions[index];
+            let g = self.kernel.force_factor(d.dot(&d), self.eps);
+            result = result.min(1. / (self.G * (self.masses[index] + self.masses[j]) * g).sqrt());
+        }
+
+        return result;
+    }
+
+    /// Without softening (or with zero softening length) the force between two distinct particles at the same position
+    /// is infinite, so such configuration is reported as an error instead of filling the state with NaNs.
+    ///
+    /// `ids`: identifiers of particles that are reported in the error
+    pub fn check_coincident(&self, positions: &[Vector3], ids: &[u64]) -> Result<(), Error> {
+        if self.kernel != SofteningKernel::None && self.eps > 0. {
+            return Ok(());
+        }
+
+        let mut order: Vec<usize> = (0..positions.len()).collect();
+        let key = |i: &usize| -> [u64; 3] {
+            // adding zero turns -0. into 0., so that equal coordinates have equal bits
+            let p = positions[*i];
+
+            return [(p.x + 0.).to_bits(), (p.y + 0.).to_bits(), (p.z + 0.).to_bits()];
+        };
+        order.sort_by_key(key);
+
+        for k in 1..order.len() {
+            let (i, j) = (order[k - 1], order[k]);
+
+            if positions[i] == positions[j] {
+                return Err(Error::NumericalFailure(format!(
+                    "particles {} and {} are at the same position, softening is needed to integrate them",
+                    ids[i], ids[j]
+                )));
+            }
+        }
+
+        return Ok(());
+    }
+
+    /// Evaluates `block` for each block of pairs; blocks are returned in fixed order.
+    fn blocks<T: Send, F: Fn(usize) -> T + Sync + Send>(&self, n: usize, block: F) -> Vec<T> {
+        let count = BLOCKS.min(n);
+
+        #[cfg(feature = "parallel")]
+        let iter = (0..count).into_par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let iter = 0..count;
+
+        return iter.map(block).collect();
+    }
+}
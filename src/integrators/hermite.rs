@@ -0,0 +1,231 @@
+use super::state::SystemState;
+use super::timestep::{defined_timescale, AdaptiveTimestep, TimestepCriterion};
+use super::Integrator;
+use crate::error::Error;
+use crate::particles::ParticleSet;
+use crate::quantity::systems::{SIUnits, UnitSystem};
+use crate::quantity::typed::{IntoTyped, TimeDimension};
+use crate::quantity::ScalarQuantity;
+use crate::softening::Softening;
+use crate::vector::Vector3;
+
+/// Fourth-order Hermite predictor-corrector integrator (Makino & Aarseth 1992) with shared timestep.
+///
+/// Accelerations and their time derivatives (jerks) are computed by direct summation over pairs of particles.
+/// Positions and velocities are predicted by Taylor series up to jerk and corrected with accelerations and jerks
+/// at predicted state, so the error of the step is `O(dt^5)`.
+///
+/// With adaptive timestep the criterion is applied to each particle and the smallest timestep is taken.
+/// `TimestepCriterion::Aarseth` uses the standard form `eta * sqrt((|a| |a''| + |a'|^2) / (|a'| |a'''| + |a''|^2))`
+/// with the second and third derivatives from the last step (simplified `eta * |a| / |a'|` before the first one);
+/// note that `eta` multiplies the square root, so `eta = 0.1` is the same as `0.01` in the original notation.
+pub struct Hermite {
+    state: SystemState,
+    timestep: f64,
+    adaptive: Option<AdaptiveTimestep>,
+    derivatives: Option<Derivatives>,
+}
+
+/// Accelerations and jerks at current state and the higher derivatives from the last step.
+struct Derivatives {
+    accelerations: Vec<Vector3>,
+    jerks: Vec<Vector3>,
+    higher: Option<(Vec<Vector3>, Vec<Vector3>)>,
+}
+
+impl Hermite {
+    /// Initialises integrator.
+    pub fn new(particle_set: &ParticleSet) -> Result<Hermite, Error> {
+        return Self::with_units(particle_set, SIUnits);
+    }
+
+    /// Initialises integrator that stores the state of the system in given units.
+    pub fn with_units(particle_set: &ParticleSet, units: impl UnitSystem + 'static) -> Result<Hermite, Error> {
+        return Ok(Hermite {
+            state: SystemState::new(particle_set, units),
+            timestep: 1.0,
+            adaptive: None,
+            derivatives: None,
+        });
+    }
+
+    /// Sets fixed timestep and turns off the adaptive one; `dt` is either typed `Time` or dynamic quantity equivalent to `Units::s`.
+    pub fn set_timestep(&mut self, dt: impl IntoTyped<f64, TimeDimension>) -> Result<(), Error> {
+        self.timestep = self.state.timestep(dt)?;
+        self.adaptive = None;
+
+        return Ok(());
+    }
+
+    /// Turns on adaptive timestep: before each step it is chosen from the current state of the system
+    /// by the criterion of `adaptive`.
+    pub fn set_adaptive_timestep(&mut self, adaptive: AdaptiveTimestep) {
+        self.adaptive = Some(adaptive);
+    }
+
+    /// Returns parameters of the adaptive timestep or `None` if the timestep is fixed.
+    pub fn get_adaptive_timestep(&self) -> Option<AdaptiveTimestep> {
+        return self.adaptive;
+    }
+
+    /// Returns fixed timestep or, if the adaptive one is used, the last step that was actually taken.
+    pub fn get_timestep(&self) -> ScalarQuantity {
+        return self.timestep * self.state.units.time();
+    }
+
+    /// Sets softening of the gravitational interaction between particles.
+    pub fn set_softening(&mut self, softening: Softening) {
+        self.state.set_softening(softening);
+        self.derivatives = None;
+    }
+
+    pub fn get_softening(&self) -> Softening {
+        return self.state.get_softening();
+    }
+
+    /// Returns accelerations and jerks at current state, computing them if they are not known.
+    fn take_derivatives(&mut self) -> Derivatives {
+        return match self.derivatives.take() {
+            Some(derivatives) => derivatives,
+            None => {
+                let (accelerations, jerks) = self
+                    .state
+                    .direct_sum()
+                    .accelerations_and_jerks(&self.state.positions, &self.state.velocities);
+
+                Derivatives {
+                    accelerations,
+                    jerks,
+                    higher: None,
+                }
+            }
+        };
+    }
+
+    fn integrate(&mut self, dt: f64) {
+        let Derivatives {
+            accelerations: a0,
+            jerks: j0,
+            ..
+        } = self.take_derivatives();
+        let n = self.state.positions.len();
+        let (dt2, dt3) = (dt * dt, dt * dt * dt);
+
+        let mut positions = Vec::with_capacity(n);
+        let mut velocities = Vec::with_capacity(n);
+
+        for i in 0..n {
+            positions.push(
+                self.state.positions[i] + self.state.velocities[i] * dt + a0[i] * (dt2 / 2.) + j0[i] * (dt3 / 6.),
+            );
+            velocities.push(self.state.velocities[i] + a0[i] * dt + j0[i] * (dt2 / 2.));
+        }
+
+        let (a1, j1) = self.state.direct_sum().accelerations_and_jerks(&positions, &velocities);
+        let mut snaps = Vec::with_capacity(n);
+        let mut crackles = Vec::with_capacity(n);
+
+        for i in 0..n {
+            let v = self.state.velocities[i] + (a0[i] + a1[i]) * (dt / 2.) + (j0[i] - j1[i]) * (dt2 / 12.);
+            let x = self.state.positions[i] + (self.state.velocities[i] + v) * (dt / 2.) + (a0[i] - a1[i]) * (dt2 / 12.);
+
+            // derivatives of the interpolating polynomial at the end of the step
+            let snap = (-6. * (a0[i] - a1[i]) - dt * (4. * j0[i] + 2. * j1[i])) / dt2;
+            let crackle = (12. * (a0[i] - a1[i]) + 6. * dt * (j0[i] + j1[i])) / dt3;
+
+            self.state.positions[i] = x;
+            self.state.velocities[i] = v;
+            snaps.push(snap + crackle * dt);
+            crackles.push(crackle);
+        }
+
+        self.derivatives = Some(Derivatives {
+            accelerations: a1,
+            jerks: j1,
+            higher: Some((snaps, crackles)),
+        });
+        self.state.model_time += dt;
+    }
+
+    /// Chooses the next timestep by the criterion of `adaptive`.
+    fn adaptive_timestep(&mut self, adaptive: &AdaptiveTimestep) -> Result<f64, Error> {
+        adaptive.check_softening(self.state.eps)?;
+
+        let derivatives = self.take_derivatives();
+        let acc = &derivatives.accelerations;
+        let jerk = &derivatives.jerks;
+
+        let timescales: Vec<f64> = match adaptive.get_criterion() {
+            TimestepCriterion::Acceleration => acc.iter().map(|a| (self.state.eps / a.mag()).sqrt()).collect(),
+            TimestepCriterion::Aarseth => match &derivatives.higher {
+                Some((snaps, crackles)) => (0..acc.len())
+                    .map(|i| {
+                        let (a, j) = (acc[i].mag(), jerk[i].mag());
+                        let (s, c) = (snaps[i].mag(), crackles[i].mag());
+
+                        defined_timescale(((a * s + j * j) / (j * c + s * s)).sqrt())
+                    })
+                    .collect(),
+                None => (0..acc.len())
+                    .map(|i| defined_timescale(acc[i].mag() / jerk[i].mag()))
+                    .collect(),
+            },
+            TimestepCriterion::FreeFall => self.state.direct_sum().free_fall_times(&self.state.positions),
+        };
+
+        self.derivatives = Some(derivatives);
+
+        let timescale = timescales.into_iter().fold(f64::INFINITY, f64::min);
+
+        return Ok(adaptive.timestep(timescale, self.state.units.as_ref()));
+    }
+}
+
+impl Integrator for Hermite {
+    fn get_state(&self) -> Result<ParticleSet, Error> {
+        return self.state.get_state();
+    }
+
+    fn evolve(&mut self, time: &ScalarQuantity) -> Result<(), Error> {
+        let time = self.state.end_time(time)?;
+
+        self.state.direct_sum().check_coincident(&self.state.positions, &self.state.ids)?;
+
+        while self.state.model_time < time {
+            let dt = match self.adaptive {
+                Some(adaptive) => self.adaptive_timestep(&adaptive)?,
+                None => self.timestep,
+            };
+
+            if self.state.model_time + dt >= time {
+                let last = time - self.state.model_time;
+
+                if self.adaptive.is_some() {
+                    self.timestep = last;
+                }
+
+                let higher = self.derivatives.as_mut().and_then(|d| d.higher.take());
+                self.integrate(last);
+                // the sum may differ from `time` by round-off error
+                self.state.model_time = time;
+
+                // interpolation over a much shorter step is dominated by round-off errors,
+                // so higher derivatives of the previous step are extrapolated instead
+                if let (Some(derivatives), Some((snaps, crackles))) = (self.derivatives.as_mut(), higher) {
+                    if last < dt / 2. {
+                        let snaps = (0..snaps.len()).map(|i| snaps[i] + crackles[i] * last).collect();
+                        derivatives.higher = Some((snaps, crackles));
+                    }
+                }
+            } else {
+                if self.adaptive.is_some() {
+                    self.timestep = dt;
+                }
+
+                self.integrate(dt);
+            }
+        }
+
+        return Ok(());
+    }
+}
@@ -0,0 +1,406 @@
+use super::state::SystemState;
+use super::Integrator;
+use crate::error::Error;
+use crate::particles::ParticleSet;
+use crate::quantity::systems::{SIUnits, UnitSystem};
+use crate::quantity::typed::{IntoTyped, TimeDimension};
+use crate::quantity::ScalarQuantity;
+use crate::softening::Softening;
+use crate::vector::Vector3;
+
+/// Gauss-Radau spacings of substeps within the step; digits are kept as published.
+#[allow(clippy::excessive_precision)]
+const NODES: [f64; 8] = [
+    0.0,
+    0.0562625605369221464656521910318,
+    0.180240691736892364987579942780,
+    0.352624717113169637373907769648,
+    0.547153626330555383001448554766,
+    0.734210177215410531523210605558,
+    0.885320946839095768090359771030,
+    0.977520613561287501891174488626,
+];
+
+/// Step is rejected if the new timestep is smaller than this fraction of the tried one;
+/// the timestep also can not grow faster than the inverse of it.
+const SAFETY_FACTOR: f64 = 0.25;
+
+/// Largest number of predictor-corrector iterations during one step.
+const MAX_ITERATIONS: usize = 12;
+
+/// Predictor-corrector iterations are stopped when relative change of the highest coefficient is below this value.
+const CONVERGENCE: f64 = 1e-16;
+
+/// Predicted coefficients are dropped if the timestep grows more than this factor.
+const MAX_PREDICTION_RATIO: f64 = 20.;
+
+/// Integrator with 15th-order Gauss-Radau quadrature and adaptive timestep (IAS15, Rein & Spiegel 2015).
+///
+/// Acceleration during the step is approximated by the polynomial `a(h) = a0 + b0 h + ... + b6 h^7`,
+/// where `h` is the fraction of the step. Its coefficients are found by predictor-corrector iterations
+/// at Gauss-Radau substeps. The timestep is chosen so that the last term `b6` relative to the acceleration
+/// does not exceed `epsilon`, which keeps the error of the step below machine precision for default `epsilon = 1e-9`.
+/// Positions and velocities are accumulated with compensated (Kahan) summation, so energy error
+/// of long integrations stays at round-off level.
+pub struct IAS15 {
+    state: SystemState,
+    epsilon: f64,
+    timestep: f64,
+    last_timestep: Option<f64>,
+    position_errors: Vec<Vector3>,
+    velocity_errors: Vec<Vector3>,
+    b: Vec<Vec<Vector3>>,
+    e: Vec<Vec<Vector3>>,
+    last_b: Vec<Vec<Vector3>>,
+    last_e: Vec<Vec<Vector3>>,
+    coefficients: Coefficients,
+}
+
+/// Matrices that convert Newton divided differences `g` of the accelerations at substeps into
+/// polynomial coefficients `b` and back: `b_k = sum c[k][j] g_j`, `g_j = sum d[j][k] b_k`.
+struct Coefficients {
+    c: [[f64; 7]; 7],
+    d: [[f64; 7]; 7],
+}
+
+impl Coefficients {
+    fn new() -> Coefficients {
+        let mut c = [[0.; 7]; 7];
+
+        // `g_j` multiplies `h (h - h_1) ... (h - h_j)`, its coefficient at `h^(k + 1)` goes into `b_k`
+        let mut polynomial = vec![0., 1.];
+
+        for j in 0..7 {
+            for k in 0..=j {
+                c[k][j] = polynomial[k + 1];
+            }
+
+            let mut next = vec![0.; polynomial.len() + 1];
+
+            for k in 0..polynomial.len() {
+                next[k + 1] += polynomial[k];
+                next[k] -= NODES[j + 1] * polynomial[k];
+            }
+
+            polynomial = next;
+        }
+
+        // `c` is upper triangular with unit diagonal, so its inverse is found by back substitution
+        let mut d = [[0.; 7]; 7];
+
+        for k in 0..7 {
+            d[k][k] = 1.;
+
+            for j in (0..k).rev() {
+                let mut sum = 0.;
+
+                for m in (j + 1)..=k {
+                    sum += c[j][m] * d[m][k];
+                }
+
+                d[j][k] = -sum;
+            }
+        }
+
+        return Coefficients { c, d };
+    }
+}
+
+/// Adds `input` to `sum`, keeping the lost low-order part in `error` (Kahan summation).
+fn add_compensated(sum: &mut Vector3, error: &mut Vector3, input: Vector3) {
+    let y = input - *error;
+    let t = *sum + y;
+    *error = (t - *sum) - y;
+    *sum = t;
+}
+
+impl IAS15 {
+    /// Initialises integrator.
+    pub fn new(particle_set: &ParticleSet) -> Result<IAS15, Error> {
+        return Self::with_units(particle_set, SIUnits);
+    }
+
+    /// Initialises integrator that stores the state of the system in given units.
+    pub fn with_units(particle_set: &ParticleSet, units: impl UnitSystem + 'static) -> Result<IAS15, Error> {
+        let n = particle_set.particles.len();
+        let zeros = vec![vec![Vector3::null_vector(); n]; 7];
+
+        return Ok(IAS15 {
+            state: SystemState::new(particle_set, units),
+            epsilon: 1e-9,
+            timestep: 1.0,
+            last_timestep: None,
+            position_errors: vec![Vector3::null_vector(); n],
+            velocity_errors: vec![Vector3::null_vector(); n],
+            b: zeros.clone(),
+            e: zeros.clone(),
+            last_b: zeros.clone(),
+            last_e: zeros,
+            coefficients: Coefficients::new(),
+        });
+    }
+
+    /// Sets the timestep that is tried on the next step; `dt` is either typed `Time` or dynamic quantity equivalent to `Units::s`.
+    /// With adaptive timestep it is only the initial guess that is corrected after the first step.
+    pub fn set_timestep(&mut self, dt: impl IntoTyped<f64, TimeDimension>) -> Result<(), Error> {
+        self.timestep = self.state.timestep(dt)?;
+
+        return Ok(());
+    }
+
+    /// Returns the timestep that will be tried on the next step.
+    pub fn get_timestep(&self) -> ScalarQuantity {
+        return self.timestep * self.state.units.time();
+    }
+
+    /// Sets accuracy parameter of the timestep control; `0` turns the control off and keeps the timestep fixed.
+    pub fn set_epsilon(&mut self, epsilon: f64) -> Result<(), Error> {
+        let epsilon_check = |e: f64| -> bool { e >= 0. && e.is_finite() };
+
+        if !epsilon_check(epsilon) {
+            return Err(Error::invalid("epsilon", epsilon, "must be non-negative"));
+        }
+
+        self.epsilon = epsilon;
+
+        return Ok(());
+    }
+
+    pub fn get_epsilon(&self) -> f64 {
+        return self.epsilon;
+    }
+
+    /// Sets softening of the gravitational interaction between particles.
+    pub fn set_softening(&mut self, softening: Softening) {
+        self.state.set_softening(softening);
+    }
+
+    pub fn get_softening(&self) -> Softening {
+        return self.state.get_softening();
+    }
+
+    /// Predicts coefficients `b` for the step that is `ratio` times longer than the last accepted one
+    /// by shifting the polynomial of that step; the error of the previous prediction is added as a correction.
+    fn predict(&mut self, ratio: f64) {
+        let n = self.state.positions.len();
+
+        if ratio > MAX_PREDICTION_RATIO {
+            for k in 0..7 {
+                self.e[k] = vec![Vector3::null_vector(); n];
+                self.b[k] = vec![Vector3::null_vector(); n];
+            }
+
+            return;
+        }
+
+        // `b_(m - 1)` of the new step is `ratio^m * sum C(k, m) b_(k - 1)` over `k >= m`
+        let mut binomial = [[0.; 8]; 8];
+
+        for k in 0..8 {
+            binomial[k][0] = 1.;
+
+            for m in 1..=k {
+                binomial[k][m] = binomial[k - 1][m - 1] + binomial[k - 1][m];
+            }
+        }
+
+        for m in 1..8 {
+            let q = ratio.powi(m as i32);
+
+            for i in 0..n {
+                let mut sum = Vector3::null_vector();
+
+                for k in m..8 {
+                    sum += binomial[k][m] * self.last_b[k - 1][i];
+                }
+
+                self.e[m - 1][i] = q * sum;
+                self.b[m - 1][i] = self.e[m - 1][i] + (self.last_b[m - 1][i] - self.last_e[m - 1][i]);
+            }
+        }
+    }
+
+    /// Position at the fraction `h` of the step `dt` predicted with current coefficients.
+    fn predicted_position(&self, i: usize, h: f64, dt: f64, a0: Vector3) -> Vector3 {
+        let b = &self.b;
+        let mut s = b[6][i] / 72.;
+        s = b[5][i] / 56. + h * s;
+        s = b[4][i] / 42. + h * s;
+        s = b[3][i] / 30. + h * s;
+        s = b[2][i] / 20. + h * s;
+        s = b[1][i] / 12. + h * s;
+        s = b[0][i] / 6. + h * s;
+        s = a0 / 2. + h * s;
+
+        return self.state.positions[i] - self.position_errors[i] + dt * h * (self.state.velocities[i] + dt * h * s);
+    }
+
+    /// Tries to make the step `dt`. Returns the timestep for the next step and whether this one was accepted.
+    fn step(&mut self, dt: f64) -> Result<(f64, bool), Error> {
+        let n = self.state.positions.len();
+
+        if let Some(last) = self.last_timestep {
+            self.predict(dt / last);
+        }
+
+        let a0 = self.state.direct_sum().accelerations(&self.state.positions);
+        let c = self.coefficients.c;
+        let d = self.coefficients.d;
+        let mut g = vec![vec![Vector3::null_vector(); n]; 7];
+
+        for j in 0..7 {
+            for k in j..7 {
+                for i in 0..n {
+                    g[j][i] += d[j][k] * self.b[k][i];
+                }
+            }
+        }
+
+        let mut last_correction = f64::INFINITY;
+        let mut last_accelerations = a0.clone();
+
+        for iteration in 0..MAX_ITERATIONS {
+            let mut max_change: f64 = 0.;
+            let mut max_acceleration: f64 = 0.;
+
+            for s in 1..8 {
+                let h = NODES[s];
+                let positions: Vec<Vector3> = (0..n).map(|i| self.predicted_position(i, h, dt, a0[i])).collect();
+                let acc = self.state.direct_sum().accelerations(&positions);
+
+                for i in 0..n {
+                    // Newton divided difference of order `s` from the ones of lower orders
+                    let mut gs = (acc[i] - a0[i]) / h;
+
+                    for m in 1..s {
+                        gs = (gs - g[m - 1][i]) / (h - NODES[m]);
+                    }
+
+                    let change = gs - g[s - 1][i];
+                    g[s - 1][i] = gs;
+
+                    for k in 0..s {
+                        self.b[k][i] += c[k][s - 1] * change;
+                    }
+
+                    if s == 7 {
+                        max_change = max_change.max(change.mag());
+                        max_acceleration = max_acceleration.max(acc[i].mag());
+                    }
+                }
+
+                if s == 7 {
+                    last_accelerations = acc;
+                }
+            }
+
+            let correction = max_change / max_acceleration;
+
+            // NaN means that there are no accelerations at all
+            if correction.is_nan() || correction < CONVERGENCE || (iteration > 1 && correction >= last_correction) {
+                break;
+            }
+
+            last_correction = correction;
+        }
+
+        let mut max_b6: f64 = 0.;
+        let mut max_acceleration: f64 = 0.;
+
+        for i in 0..n {
+            max_b6 = max_b6.max(self.b[6][i].mag());
+            max_acceleration = max_acceleration.max(last_accelerations[i].mag());
+        }
+
+        let error = max_b6 / max_acceleration;
+        let mut next = dt;
+
+        if self.epsilon > 0. {
+            next = if error.is_finite() && error > 0. {
+                dt * (self.epsilon / error).powf(1. / 7.)
+            } else {
+                dt / SAFETY_FACTOR
+            };
+
+            if !(next.is_finite() && next > 0.) {
+                return Err(Error::NumericalFailure(format!(
+                    "timestep of IAS15 became {} at time {}",
+                    next,
+                    self.state.model_time * self.state.units.time()
+                )));
+            }
+
+            if next < SAFETY_FACTOR * dt {
+                return Ok((next, false));
+            }
+
+            next = next.min(dt / SAFETY_FACTOR);
+        }
+
+        let b = &self.b;
+
+        for i in 0..n {
+            let x = &mut self.state.positions[i];
+            let x_error = &mut self.position_errors[i];
+            let dt2 = dt * dt;
+
+            add_compensated(x, x_error, self.state.velocities[i] * dt);
+            add_compensated(x, x_error, a0[i] * (dt2 / 2.));
+
+            for k in 0..7 {
+                add_compensated(x, x_error, b[k][i] * (dt2 / ((k + 2) * (k + 3)) as f64));
+            }
+
+            let v = &mut self.state.velocities[i];
+            let v_error = &mut self.velocity_errors[i];
+
+            add_compensated(v, v_error, a0[i] * dt);
+
+            for k in 0..7 {
+                add_compensated(v, v_error, b[k][i] * (dt / (k + 2) as f64));
+            }
+        }
+
+        self.last_b = self.b.clone();
+        self.last_e = self.e.clone();
+        self.last_timestep = Some(dt);
+        self.state.model_time += dt;
+
+        return Ok((next, true));
+    }
+}
+
+impl Integrator for IAS15 {
+    fn get_state(&self) -> Result<ParticleSet, Error> {
+        return self.state.get_state();
+    }
+
+    fn evolve(&mut self, time: &ScalarQuantity) -> Result<(), Error> {
+        let time = self.state.end_time(time)?;
+
+        self.state.direct_sum().check_coincident(&self.state.positions, &self.state.ids)?;
+
+        while self.state.model_time < time {
+            let remaining = time - self.state.model_time;
+            let truncated = self.timestep >= remaining;
+            let dt = if truncated { remaining } else { self.timestep };
+            let (next, accepted) = self.step(dt)?;
+
+            if !accepted {
+                self.timestep = next;
+                continue;
+            }
+
+            if truncated {
+                // the sum may differ from `time` by round-off error
+                self.state.model_time = time;
+                // the step was shortened to land on `time`, so it says little about the timestep of the system
+                self.timestep = next.max(self.timestep);
+            } else {
+                self.timestep = next;
+            }
+        }
+
+        return Ok(());
+    }
+}
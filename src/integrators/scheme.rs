@@ -0,0 +1,89 @@
+use crate::vector::Vector3;
+
+/// Method that is used to advance positions and velocities of particles by one timestep.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Scheme {
+    /// Semi-implicit (symplectic) Euler method: kick with current accelerations, then drift.
+    Euler,
+    /// Kick-drift-kick leapfrog: half kick, full drift, half kick.
+    LeapfrogKDK,
+    /// Velocity Verlet: drift with accelerations of the start of the step, then kick with the mean of old and new accelerations.
+    VelocityVerlet,
+    /// Classical fourth-order Runge-Kutta method.
+    RungeKutta4,
+}
+
+impl Scheme {
+    /// Advances `positions` and `velocities` by `dt`.
+    ///
+    /// `accelerations`: accelerations at `positions` if they are already known; they are updated to the ones at the end of the step
+    /// `get_accelerations`: kernel that returns accelerations of particles at given positions
+    pub(crate) fn step<F>(
+        &self,
+        positions: &mut [Vector3],
+        velocities: &mut [Vector3],
+        accelerations: &mut Option<Vec<Vector3>>,
+        dt: f64,
+        get_accelerations: F,
+    ) where
+        F: Fn(&[Vector3]) -> Vec<Vector3>,
+    {
+        let acc = match accelerations.take() {
+            Some(acc) => acc,
+            None => get_accelerations(positions),
+        };
+
+        match self {
+            Self::Euler => {
+                add_scaled(velocities, &acc, dt);
+                add_scaled(positions, velocities, dt);
+            }
+            Self::LeapfrogKDK => {
+                add_scaled(velocities, &acc, dt / 2.);
+                add_scaled(positions, velocities, dt);
+
+                let new_acc = get_accelerations(positions);
+                add_scaled(velocities, &new_acc, dt / 2.);
+                *accelerations = Some(new_acc);
+            }
+            Self::VelocityVerlet => {
+                for i in 0..positions.len() {
+                    positions[i] += velocities[i] * dt + acc[i] * (dt * dt / 2.);
+                }
+
+                let new_acc = get_accelerations(positions);
+
+                for i in 0..velocities.len() {
+                    velocities[i] += (acc[i] + new_acc[i]) * (dt / 2.);
+                }
+
+                *accelerations = Some(new_acc);
+            }
+            Self::RungeKutta4 => {
+                let k1x = velocities.to_vec();
+                let k1v = acc;
+                let k2x = shifted(velocities, &k1v, dt / 2.);
+                let k2v = get_accelerations(&shifted(positions, &k1x, dt / 2.));
+                let k3x = shifted(velocities, &k2v, dt / 2.);
+                let k3v = get_accelerations(&shifted(positions, &k2x, dt / 2.));
+                let k4x = shifted(velocities, &k3v, dt);
+                let k4v = get_accelerations(&shifted(positions, &k3x, dt));
+
+                for i in 0..positions.len() {
+                    positions[i] += (k1x[i] + 2. * k2x[i] + 2. * k3x[i] + k4x[i]) * (dt / 6.);
+                    velocities[i] += (k1v[i] + 2. * k2v[i] + 2. * k3v[i] + k4v[i]) * (dt / 6.);
+                }
+            }
+        }
+    }
+}
+
+fn add_scaled(target: &mut [Vector3], v: &[Vector3], h: f64) {
+    for i in 0..target.len() {
+        target[i] += v[i] * h;
+    }
+}
+
+fn shifted(base: &[Vector3], v: &[Vector3], h: f64) -> Vec<Vector3> {
+    return (0..base.len()).map(|i| base[i] + v[i] * h).collect();
+}
@@ -1,147 +1,231 @@
+use super::scheme::Scheme;
+use super::state::{fixed_steps, SystemState};
+use super::timestep::{defined_timescale, AdaptiveTimestep, TimestepCriterion};
 use super::Integrator;
-use crate::particles::{Particle, ParticleSet};
-use crate::quantity::{ScalarQuantity, Units};
+use crate::error::Error;
+use crate::particles::ParticleSet;
+use crate::quantity::typed::{IntoTyped, TimeDimension};
+use crate::quantity::systems::{SIUnits, UnitSystem};
+use crate::quantity::{ScalarQuantity, VectorQuantity};
+use crate::softening::Softening;
 use crate::vector::Vector3;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Way of direct summation of the forces between particles.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Summation {
+    /// Each pair is visited once and gives equal and opposite accelerations to its particles (Newton's third law),
+    /// so the total momentum is conserved up to round-off errors.
+    Pairwise,
+    /// Acceleration of each particle is summed independently, so each pair is visited twice.
+    PerParticle,
+}
 
-/// Integrator that uses direct summation and Euler method for numerical integration.
-#[allow(non_snake_case)]
+/// Integrator that uses direct summation and one of `Scheme` methods for numerical integration.
 pub struct SimpleNBody {
-    positions: Vec<Vector3>,
-    velocities: Vec<Vector3>,
-    masses: Vec<f64>,
-    G: f64,
+    state: SystemState,
     timestep: f64,
-    model_time: f64
+    adaptive: Option<AdaptiveTimestep>,
+    scheme: Scheme,
+    summation: Summation,
+    accelerations: Option<Vec<Vector3>>,
 }
 
 impl SimpleNBody {
-    /// Initialises integrator.
-    pub fn new(particle_set: &ParticleSet) -> Result<SimpleNBody, &'static str> {
-        let positions = particle_set
-            .particles
-            .iter()
-            .map(|p| -> Vector3 { p.get_position().value_in(Units::m) })
-            .collect();
-        let velocities = particle_set
-            .particles
-            .iter()
-            .map(|p| -> Vector3 { p.get_velocity().value_in(Units::ms) })
-            .collect();
-        let masses = particle_set
-            .particles
-            .iter()
-            .map(|p| -> f64 { p.get_mass().value_in(Units::kg) })
-            .collect();
+    /// Initialises integrator that uses semi-implicit Euler method.
+    pub fn new(particle_set: &ParticleSet) -> Result<SimpleNBody, Error> {
+        return Self::with_scheme(particle_set, Scheme::Euler);
+    }
 
+    /// Initialises integrator that uses given integration scheme.
+    pub fn with_scheme(particle_set: &ParticleSet, scheme: Scheme) -> Result<SimpleNBody, Error> {
+        return Self::with_units(particle_set, scheme, SIUnits);
+    }
+
+    /// Initialises integrator that uses given integration scheme and stores the state of the system in given units.
+    /// Units close to the scales of the system (for example, `NBodyUnits`) reduce round-off errors;
+    /// all the quantities are still passed and returned in physical units.
+    pub fn with_units(
+        particle_set: &ParticleSet,
+        scheme: Scheme,
+        units: impl UnitSystem + 'static,
+    ) -> Result<SimpleNBody, Error> {
         return Ok(SimpleNBody {
-            positions,
-            velocities,
-            masses,
-            G: Units::G
-                .convert()
-                .value_in_q(Units::m.pow(3.) * Units::kg.pow(-1.) * Units::s.pow(-2.)),
+            state: SystemState::new(particle_set, units),
             timestep: 1.0,
-            model_time: 0.0
+            adaptive: None,
+            scheme,
+            summation: Summation::Pairwise,
+            accelerations: None,
         });
     }
 
-    pub fn set_timestep(&mut self, dt: ScalarQuantity) -> Result<(), String> {
-        if !dt.is_compatible(1.0 * Units::s) {
-            return Err("dt has incompatible units".to_string());
-        }
-
-        self.timestep = dt.value_in(Units::s);
+    /// Sets fixed timestep and turns off the adaptive one; `dt` is either typed `Time` or dynamic quantity equivalent to `Units::s`.
+    pub fn set_timestep(&mut self, dt: impl IntoTyped<f64, TimeDimension>) -> Result<(), Error> {
+        self.timestep = self.state.timestep(dt)?;
+        self.adaptive = None;
 
         return Ok(());
     }
 
-    pub fn get_timestep(&self) -> ScalarQuantity {
-        return self.timestep * Units::s;
+    /// Turns on adaptive timestep: before each step it is chosen from the current state of the system
+    /// by the criterion of `adaptive`.
+    pub fn set_adaptive_timestep(&mut self, adaptive: AdaptiveTimestep) {
+        self.adaptive = Some(adaptive);
     }
 
-    fn integrate(&mut self, dt: f64) {
-        let mut force: Vector3;
-        let mut dvs = vec![Vector3::null_vector(); self.positions.len()];
-        let mut drs = vec![Vector3::null_vector(); self.positions.len()];
-
-        for i in 0..self.positions.len() {
-            force = self.get_force_to_particle(self.positions[i], self.masses[i]);
-            dvs[i] = force / self.masses[i] * dt;
-        }
-
-        self.update_velocities(dvs);
+    /// Returns parameters of the adaptive timestep or `None` if the timestep is fixed.
+    pub fn get_adaptive_timestep(&self) -> Option<AdaptiveTimestep> {
+        return self.adaptive;
+    }
 
-        for i in 0..self.positions.len() {
-            drs[i] = self.velocities[i] * dt;
-        }
+    /// Returns fixed timestep or, if the adaptive one is used, the last step that was actually taken.
+    pub fn get_timestep(&self) -> ScalarQuantity {
+        return self.timestep * self.state.units.time();
+    }
 
-        self.update_positions(drs);
+    /// Sets softening of the gravitational interaction between particles.
+    pub fn set_softening(&mut self, softening: Softening) {
+        self.state.set_softening(softening);
+        self.accelerations = None;
+    }
 
-        self.model_time += dt;
+    pub fn get_softening(&self) -> Softening {
+        return self.state.get_softening();
     }
 
-    fn get_force(&self, pos1: Vector3, pos2: Vector3, m1: f64, m2: f64) -> Vector3 {
-        let dist = pos1 - pos2;
+    pub fn get_scheme(&self) -> Scheme {
+        return self.scheme;
+    }
 
-        return self.G * m1 * m2 / (dist.dot(&dist)) * dist.unit();
+    /// Sets the way of summation of the forces; `Summation::Pairwise` is used by default.
+    pub fn set_summation(&mut self, summation: Summation) {
+        self.summation = summation;
+        self.accelerations = None;
     }
 
-    fn update_velocities(&mut self, dvs: Vec<Vector3>) {
-        for i in 0..self.velocities.len() {
-            self.velocities[i] += dvs[i];
-        }
+    pub fn get_summation(&self) -> Summation {
+        return self.summation;
     }
 
-    fn update_positions(&mut self, drs: Vec<Vector3>) {
-        for i in 0..self.positions.len() {
-            self.positions[i] += drs[i];
-        }
+    /// Returns accelerations of particles in the current state of the system.
+    ///
+    /// `return`: `VectorQuantity` equivalent to `Units::ms / Units::s` for each particle
+    pub fn get_accelerations(&self) -> Vec<VectorQuantity> {
+        return self
+            .compute_accelerations(&self.state.positions)
+            .into_iter()
+            .map(|a| a * self.state.units.acceleration())
+            .collect();
     }
 
-    fn get_force_to_particle(&self, pos: Vector3, m: f64) -> Vector3 {
-        let mut result = Vector3::null_vector();
+    fn integrate(&mut self, dt: f64) {
+        let scheme = self.scheme;
+        let mut positions = std::mem::take(&mut self.state.positions);
+        let mut velocities = std::mem::take(&mut self.state.velocities);
+        let mut accelerations = self.accelerations.take();
+
+        scheme.step(
+            &mut positions,
+            &mut velocities,
+            &mut accelerations,
+            dt,
+            |pos| self.compute_accelerations(pos),
+        );
+
+        self.state.positions = positions;
+        self.state.velocities = velocities;
+        self.accelerations = accelerations;
+        self.state.model_time += dt;
+    }
 
-        for i in 0..self.positions.len() {
-            if self.positions[i] == pos {
-                continue;
+    /// Chooses the next timestep by the criterion of `adaptive`; accelerations that are computed on the way
+    /// are kept for the next step.
+    fn adaptive_timestep(&mut self, adaptive: &AdaptiveTimestep) -> Result<f64, Error> {
+        adaptive.check_softening(self.state.eps)?;
+
+        let timescales: Vec<f64> = match adaptive.get_criterion() {
+            TimestepCriterion::Acceleration => {
+                let acc = match self.accelerations.take() {
+                    Some(acc) => acc,
+                    None => self.compute_accelerations(&self.state.positions),
+                };
+                let timescales = acc.iter().map(|a| (self.state.eps / a.mag()).sqrt()).collect();
+                self.accelerations = Some(acc);
+
+                timescales
+            }
+            TimestepCriterion::Aarseth => {
+                let (acc, jerk) = self
+                    .state
+                    .direct_sum()
+                    .accelerations_and_jerks(&self.state.positions, &self.state.velocities);
+                let timescales = (0..acc.len())
+                    .map(|i| defined_timescale(acc[i].mag() / jerk[i].mag()))
+                    .collect();
+
+                if self.summation == Summation::Pairwise {
+                    self.accelerations = Some(acc);
+                }
+
+                timescales
             }
+            TimestepCriterion::FreeFall => self.state.direct_sum().free_fall_times(&self.state.positions),
+        };
+
+        let timescale = timescales.into_iter().fold(f64::INFINITY, f64::min);
+
+        return Ok(adaptive.timestep(timescale, self.state.units.as_ref()));
+    }
 
-            result += self.get_force(self.positions[i], pos, self.masses[i], m);
+    fn compute_accelerations(&self, positions: &[Vector3]) -> Vec<Vector3> {
+        if self.summation == Summation::Pairwise {
+            return self.state.direct_sum().accelerations(positions);
         }
 
-        return result;
+        #[cfg(feature = "parallel")]
+        let iter = (0..positions.len()).into_par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let iter = 0..positions.len();
+
+        return iter
+            .map(|i| self.state.direct_sum().acceleration_of(i, positions))
+            .collect();
     }
 }
 
 impl Integrator for SimpleNBody {
-    fn get_state(&self) -> Result<ParticleSet, &'static str> {
-        let mut result = ParticleSet::new()?;
+    fn get_state(&self) -> Result<ParticleSet, Error> {
+        return self.state.get_state();
+    }
 
-        for i in 0..self.positions.len() {
-            let p = Particle::new(
-                self.positions[i] * Units::m,
-                self.velocities[i] * Units::ms,
-                self.masses[i] * Units::kg,
-            )?;
+    fn evolve(&mut self, time: &ScalarQuantity) -> Result<(), Error> {
+        let time = self.state.end_time(time)?;
 
-            result.add_particle(p);
-        }
+        self.state.direct_sum().check_coincident(&self.state.positions, &self.state.ids)?;
 
-        return Ok(result);
-    }
+        if let Some(adaptive) = self.adaptive {
+            while self.state.model_time < time {
+                let dt = self.adaptive_timestep(&adaptive)?;
 
-    fn evolve(&mut self, time: &ScalarQuantity) -> Result<(), String> {
-        if !time.is_compatible(1. * Units::s) {
-            return Err("time has wrong units".to_string());
-        }
-
-        let time = time.value_in(Units::s);
+                if self.state.model_time + dt >= time {
+                    self.timestep = time - self.state.model_time;
+                    self.integrate(self.timestep);
+                    // the sum may differ from `time` by round-off error
+                    self.state.model_time = time;
+                } else {
+                    self.timestep = dt;
+                    self.integrate(dt);
+                }
+            }
 
-        while self.model_time <= (time - self.timestep) {
-            self.integrate(self.timestep);
+            return Ok(());
         }
 
-        self.integrate(time - self.model_time);
+        for dt in fixed_steps(self.state.model_time, time, self.timestep) {
+            self.integrate(dt);
+        }
 
         return Ok(());
     }
@@ -0,0 +1,142 @@
+use super::direct::DirectSum;
+use crate::error::Error;
+use crate::particles::{Particle, ParticleSet};
+use crate::quantity::systems::UnitSystem;
+use crate::quantity::typed::{IntoTyped, TimeDimension};
+use crate::quantity::{ScalarQuantity, Units};
+use crate::softening::{Softening, SofteningKernel};
+use crate::vector::Vector3;
+
+/// Particles of the system in the units of the integrator, their softening and the model time.
+/// It is the part of the state that is the same for all integrators that store particles as they are.
+#[allow(non_snake_case)]
+pub(crate) struct SystemState {
+    pub ids: Vec<u64>,
+    pub positions: Vec<Vector3>,
+    pub velocities: Vec<Vector3>,
+    pub masses: Vec<f64>,
+    pub G: f64,
+    pub model_time: f64,
+    pub kernel: SofteningKernel,
+    pub eps: f64,
+    pub units: Box<dyn UnitSystem>,
+}
+
+impl SystemState {
+    /// Converts particles of `particle_set` into `units`; softening is turned off.
+    pub fn new(particle_set: &ParticleSet, units: impl UnitSystem + 'static) -> SystemState {
+        let ids = particle_set
+            .particles
+            .iter()
+            .map(|p| -> u64 { p.get_id() })
+            .collect();
+        let positions = particle_set
+            .particles
+            .iter()
+            .map(|p| -> Vector3 { p.get_position().value_in_system(&units) })
+            .collect();
+        let velocities = particle_set
+            .particles
+            .iter()
+            .map(|p| -> Vector3 { p.get_velocity().value_in_system(&units) })
+            .collect();
+        let masses = particle_set
+            .particles
+            .iter()
+            .map(|p| -> f64 { p.get_mass().value_in_system(&units) })
+            .collect();
+
+        return SystemState {
+            ids,
+            positions,
+            velocities,
+            masses,
+            G: units.gravitational_constant(),
+            model_time: 0.0,
+            kernel: SofteningKernel::None,
+            eps: 0.0,
+            units: Box::new(units),
+        };
+    }
+
+    /// Particles in physical units with the identifiers of the ones that the state was created from.
+    pub fn get_state(&self) -> Result<ParticleSet, Error> {
+        let mut result = ParticleSet::new()?;
+
+        for i in 0..self.positions.len() {
+            let mut p = Particle::new(
+                self.positions[i] * self.units.length(),
+                self.velocities[i] * self.units.velocity(),
+                self.masses[i] * self.units.mass(),
+            )?;
+            p.set_id(self.ids[i]);
+
+            result.restore_particle(p);
+        }
+
+        return Ok(result);
+    }
+
+    pub fn set_softening(&mut self, softening: Softening) {
+        self.kernel = softening.get_kernel();
+        self.eps = softening.get_length().value_in_system(self.units.as_ref());
+    }
+
+    pub fn get_softening(&self) -> Softening {
+        return Softening::new(self.kernel, self.eps * self.units.length()).unwrap();
+    }
+
+    /// Timestep in the units of the state; `dt` is either typed `Time` or dynamic quantity equivalent to `Units::s`
+    /// and must be positive and finite.
+    pub fn timestep(&self, dt: impl IntoTyped<f64, TimeDimension>) -> Result<f64, Error> {
+        let dt_check = |dt: f64| -> bool { dt > 0. && dt.is_finite() };
+        let dt = dt.into_typed("timestep")?;
+        let value = dt.get_quantity().value_in_system(self.units.as_ref());
+
+        if !dt_check(value) {
+            return Err(Error::invalid("timestep", dt, "must be positive"));
+        }
+
+        return Ok(value);
+    }
+
+    /// End time of `evolve` call in the units of the state.
+    pub fn end_time(&self, time: &ScalarQuantity) -> Result<f64, Error> {
+        time.check_compatibility("time", Units::s.convert())?;
+
+        return Ok(time.value_in_system(self.units.as_ref()));
+    }
+
+    pub fn direct_sum(&self) -> DirectSum<'_> {
+        return DirectSum {
+            masses: &self.masses,
+            G: self.G,
+            kernel: self.kernel,
+            eps: self.eps,
+        };
+    }
+}
+
+/// Lengths of the steps that take the system from `start` to `end` with fixed timestep `dt`:
+/// full steps while they fit and then the remainder, which lands on `end`. `dt` must be positive
+/// (see `SystemState::timestep`), otherwise the steps never end.
+pub(crate) fn fixed_steps(start: f64, end: f64, dt: f64) -> impl Iterator<Item = f64> {
+    let mut time = start;
+    let mut finished = false;
+
+    return std::iter::from_fn(move || {
+        if finished {
+            return None;
+        }
+
+        if time <= end - dt {
+            time += dt;
+
+            return Some(dt);
+        }
+
+        finished = true;
+
+        return Some(end - time);
+    });
+}
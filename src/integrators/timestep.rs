@@ -0,0 +1,110 @@
+use crate::error::Error;
+use crate::quantity::systems::UnitSystem;
+use crate::quantity::typed::{IntoTyped, Time, TimeDimension};
+
+/// Maps undefined timescale (e.g. `0 / 0` of Aarseth criterion for a particle without acceleration and jerk)
+/// to infinity: such a particle does not limit the timestep.
+pub(crate) fn defined_timescale(timescale: f64) -> f64 {
+    if timescale.is_nan() {
+        return f64::INFINITY;
+    }
+
+    return timescale;
+}
+
+/// Criterion that gives the timestep of each particle; the smallest of them is taken.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum TimestepCriterion {
+    /// `eta * sqrt(eps / |a|)`, where `eps` is the softening length and `a` is the acceleration of the particle;
+    /// it requires non-zero softening length.
+    Acceleration,
+    /// Simplified Aarseth criterion `eta * |a| / |da/dt|`.
+    Aarseth,
+    /// `eta * t_ff`, where `t_ff` is the shortest free-fall time of the particle and one of its partners
+    /// (`sqrt(r^3 / (G * (m_i + m_j)))` without softening).
+    FreeFall,
+}
+
+/// Parameters of the adaptive choice of the timestep: criterion, its accuracy parameter `eta`
+/// and bounds of the timestep.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct AdaptiveTimestep {
+    criterion: TimestepCriterion,
+    eta: f64,
+    min: Time,
+    max: Time,
+}
+
+impl AdaptiveTimestep {
+    /// Creates new parameters of the adaptive timestep.
+    ///
+    /// `eta`: positive dimensionless accuracy parameter, `min` and `max`: positive bounds of the timestep, `min <= max`
+    pub fn new(
+        criterion: TimestepCriterion,
+        eta: f64,
+        min: impl IntoTyped<f64, TimeDimension>,
+        max: impl IntoTyped<f64, TimeDimension>,
+    ) -> Result<AdaptiveTimestep, Error> {
+        let eta_check = |eta: f64| -> bool { eta > 0. && eta.is_finite() };
+        let min_check = |min: &Time| -> bool { *min > Time::from_si(0.) };
+        let max_check = |min: &Time, max: &Time| -> bool { *max >= *min };
+
+        let min = min.into_typed("minimal timestep")?;
+        let max = max.into_typed("maximal timestep")?;
+
+        if !eta_check(eta) {
+            return Err(Error::invalid("eta", eta, "must be positive"));
+        }
+        if !min_check(&min) {
+            return Err(Error::invalid("minimal timestep", min, "must be positive"));
+        }
+        if !max_check(&min, &max) {
+            return Err(Error::invalid("maximal timestep", max, "must not be less than minimal timestep"));
+        }
+
+        return Ok(AdaptiveTimestep {
+            criterion,
+            eta,
+            min,
+            max,
+        });
+    }
+
+    pub fn get_criterion(&self) -> TimestepCriterion {
+        return self.criterion;
+    }
+
+    pub fn get_eta(&self) -> f64 {
+        return self.eta;
+    }
+
+    pub fn get_min(&self) -> Time {
+        return self.min;
+    }
+
+    pub fn get_max(&self) -> Time {
+        return self.max;
+    }
+
+    /// Returns error if the criterion can not be used with softening length `eps`.
+    pub(crate) fn check_softening(&self, eps: f64) -> Result<(), Error> {
+        if self.criterion == TimestepCriterion::Acceleration && eps == 0. {
+            return Err(Error::invalid(
+                "timestep criterion",
+                "acceleration",
+                "requires non-zero softening length",
+            ));
+        }
+
+        return Ok(());
+    }
+
+    /// Returns timestep in units of `units` for given smallest timescale of particles.
+    /// Undefined timescale gives the maximal timestep (see `defined_timescale`).
+    pub(crate) fn timestep(&self, timescale: f64, units: &dyn UnitSystem) -> f64 {
+        let min = self.min.get_quantity().value_in_system(units);
+        let max = self.max.get_quantity().value_in_system(units);
+
+        return (self.eta * defined_timescale(timescale)).max(min).min(max);
+    }
+}
@@ -0,0 +1,284 @@
+use super::state::{fixed_steps, SystemState};
+use super::Integrator;
+use crate::error::Error;
+use crate::kepler;
+use crate::particles::{Particle, ParticleSet};
+use crate::quantity::systems::{SIUnits, UnitSystem};
+use crate::quantity::typed::{IntoTyped, TimeDimension};
+use crate::quantity::ScalarQuantity;
+use crate::softening::Softening;
+use crate::vector::Vector3;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Way to designate the dominant body of the system.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum CentralBody {
+    /// Index of the body in `ParticleSet`.
+    Index(usize),
+    /// Identifier of the body.
+    Id(u64),
+}
+
+/// Wisdom-Holman symplectic map for systems with one dominant body, in democratic heliocentric coordinates
+/// (Duncan, Levison & Lee 1998).
+///
+/// Positions of other bodies are taken relative to the central one and velocities relative to the centre of mass.
+/// Each step is `kick(dt/2) jump(dt/2) drift(dt) jump(dt/2) kick(dt/2)`, where drift moves every body along
+/// its Keplerian orbit around the central one, kick applies interaction between non-central bodies
+/// and jump shifts positions by the momentum of the central body. Energy error does not grow with time and is
+/// proportional to the ratio of the masses of other bodies and the central one.
+///
+/// Softening is applied only to the interaction between non-central bodies.
+pub struct WisdomHolman {
+    /// Non-central bodies in democratic heliocentric coordinates.
+    state: SystemState,
+    central: usize,
+    central_id: u64,
+    central_mass: f64,
+    others: Vec<usize>,
+    center_of_mass: Vector3,
+    center_of_mass_velocity: Vector3,
+    timestep: f64,
+}
+
+impl WisdomHolman {
+    /// Initialises integrator for the system dominated by `central` body.
+    pub fn new(particle_set: &ParticleSet, central: CentralBody) -> Result<WisdomHolman, Error> {
+        return Self::with_units(particle_set, central, SIUnits);
+    }
+
+    /// Initialises integrator for the system dominated by `central` body that stores the state of the system in given units.
+    pub fn with_units(
+        particle_set: &ParticleSet,
+        central: CentralBody,
+        units: impl UnitSystem + 'static,
+    ) -> Result<WisdomHolman, Error> {
+        let set = &particle_set.particles;
+
+        if set.is_empty() {
+            return Err(Error::EmptySet);
+        }
+
+        let central = match central {
+            CentralBody::Index(index) => {
+                if index >= set.len() {
+                    return Err(Error::invalid("central body index", index, "is out of range"));
+                }
+
+                index
+            }
+            CentralBody::Id(id) => match set.iter().position(|p| p.get_id() == id) {
+                Some(index) => index,
+                None => return Err(Error::invalid("central body id", id, "is not found")),
+            },
+        };
+
+        let mass_check = |m: f64| -> bool { m > 0. };
+        let central_mass = set[central].get_mass().value_in_system(&units);
+
+        if !mass_check(central_mass) {
+            return Err(Error::invalid("central body mass", set[central].get_mass(), "must be positive"));
+        }
+
+        let others: Vec<usize> = (0..set.len()).filter(|i| *i != central).collect();
+        let mut state = SystemState::new(particle_set, units);
+        let (x, v, m) = (&state.positions, &state.velocities, &state.masses);
+
+        let mut total_mass = 0.;
+        let mut center_of_mass = Vector3::null_vector();
+        let mut center_of_mass_velocity = Vector3::null_vector();
+
+        for i in 0..set.len() {
+            total_mass += m[i];
+            center_of_mass += m[i] * x[i];
+            center_of_mass_velocity += m[i] * v[i];
+        }
+
+        center_of_mass /= total_mass;
+        center_of_mass_velocity /= total_mass;
+
+        let positions = others.iter().map(|i| x[*i] - x[central]).collect();
+        let velocities = others.iter().map(|i| v[*i] - center_of_mass_velocity).collect();
+        let masses = others.iter().map(|i| m[*i]).collect();
+        let central_id = state.ids[central];
+
+        state.ids = others.iter().map(|i| state.ids[*i]).collect();
+        state.positions = positions;
+        state.velocities = velocities;
+        state.masses = masses;
+
+        return Ok(WisdomHolman {
+            state,
+            central,
+            central_id,
+            central_mass,
+            others,
+            center_of_mass,
+            center_of_mass_velocity,
+            timestep: 1.0,
+        });
+    }
+
+    /// Sets timestep; `dt` is either typed `Time` or dynamic quantity equivalent to `Units::s`.
+    pub fn set_timestep(&mut self, dt: impl IntoTyped<f64, TimeDimension>) -> Result<(), Error> {
+        self.timestep = self.state.timestep(dt)?;
+
+        return Ok(());
+    }
+
+    pub fn get_timestep(&self) -> ScalarQuantity {
+        return self.timestep * self.state.units.time();
+    }
+
+    /// Sets softening of the gravitational interaction between non-central bodies.
+    pub fn set_softening(&mut self, softening: Softening) {
+        self.state.set_softening(softening);
+    }
+
+    pub fn get_softening(&self) -> Softening {
+        return self.state.get_softening();
+    }
+
+    /// Returns identifier of the central body.
+    pub fn get_central_id(&self) -> u64 {
+        return self.central_id;
+    }
+
+    fn kick(&self, positions: &[Vector3], velocities: &mut [Vector3], dt: f64) {
+        let acc = self.state.direct_sum().accelerations(positions);
+
+        for i in 0..velocities.len() {
+            velocities[i] += acc[i] * dt;
+        }
+    }
+
+    fn jump(&self, positions: &mut [Vector3], velocities: &[Vector3], dt: f64) {
+        let mut momentum = Vector3::null_vector();
+
+        for i in 0..velocities.len() {
+            momentum += self.state.masses[i] * velocities[i];
+        }
+
+        let shift = momentum * (dt / self.central_mass);
+
+        for x in positions.iter_mut() {
+            *x += shift;
+        }
+    }
+
+    fn drift(&self, positions: &mut [Vector3], velocities: &mut [Vector3], dt: f64) -> Result<(), Error> {
+        let mu = self.state.G * self.central_mass;
+        let orbit = |(x, v): (&Vector3, &Vector3)| -> Result<(Vector3, Vector3), Error> {
+            return kepler::advance(*x, *v, mu, dt);
+        };
+
+        #[cfg(feature = "parallel")]
+        let states: Result<Vec<(Vector3, Vector3)>, Error> =
+            positions.par_iter().zip(velocities.par_iter()).map(orbit).collect();
+        #[cfg(not(feature = "parallel"))]
+        let states: Result<Vec<(Vector3, Vector3)>, Error> =
+            positions.iter().zip(velocities.iter()).map(orbit).collect();
+
+        let states = states?;
+
+        for i in 0..states.len() {
+            positions[i] = states[i].0;
+            velocities[i] = states[i].1;
+        }
+
+        return Ok(());
+    }
+
+    /// Makes one step on copies of positions and velocities; the state is changed only if the whole step succeeds.
+    fn integrate(&mut self, dt: f64) -> Result<(), Error> {
+        let mut positions = self.state.positions.clone();
+        let mut velocities = self.state.velocities.clone();
+
+        self.kick(&positions, &mut velocities, dt / 2.);
+        self.jump(&mut positions, &velocities, dt / 2.);
+        self.drift(&mut positions, &mut velocities, dt)?;
+        self.jump(&mut positions, &velocities, dt / 2.);
+        self.kick(&positions, &mut velocities, dt / 2.);
+
+        self.state.positions = positions;
+        self.state.velocities = velocities;
+        self.state.model_time += dt;
+
+        return Ok(());
+    }
+}
+
+impl Integrator for WisdomHolman {
+    fn get_state(&self) -> Result<ParticleSet, Error> {
+        let n = self.others.len() + 1;
+        let mut total_mass = self.central_mass;
+        let mut weighted_position = Vector3::null_vector();
+        let mut momentum = Vector3::null_vector();
+
+        for i in 0..self.state.positions.len() {
+            total_mass += self.state.masses[i];
+            weighted_position += self.state.masses[i] * self.state.positions[i];
+            momentum += self.state.masses[i] * self.state.velocities[i];
+        }
+
+        let center_of_mass = self.center_of_mass + self.center_of_mass_velocity * self.state.model_time;
+        let central_position = center_of_mass - weighted_position / total_mass;
+
+        let mut positions = vec![Vector3::null_vector(); n];
+        let mut velocities = vec![Vector3::null_vector(); n];
+        let mut masses = vec![0.; n];
+        let mut ids = vec![0; n];
+
+        positions[self.central] = central_position;
+        velocities[self.central] = self.center_of_mass_velocity - momentum / self.central_mass;
+        masses[self.central] = self.central_mass;
+        ids[self.central] = self.central_id;
+
+        for k in 0..self.others.len() {
+            let i = self.others[k];
+            positions[i] = central_position + self.state.positions[k];
+            velocities[i] = self.center_of_mass_velocity + self.state.velocities[k];
+            masses[i] = self.state.masses[k];
+            ids[i] = self.state.ids[k];
+        }
+
+        let mut result = ParticleSet::new()?;
+
+        for i in 0..n {
+            let mut p = Particle::new(
+                positions[i] * self.state.units.length(),
+                velocities[i] * self.state.units.velocity(),
+                masses[i] * self.state.units.mass(),
+            )?;
+            p.set_id(ids[i]);
+
+            result.restore_particle(p);
+        }
+
+        return Ok(result);
+    }
+
+    fn evolve(&mut self, time: &ScalarQuantity) -> Result<(), Error> {
+        let time = self.state.end_time(time)?;
+
+        self.state.direct_sum().check_coincident(&self.state.positions, &self.state.ids)?;
+
+        // Kepler drift is not defined for a body at the position of the central one
+        if let Some(i) = self.state.positions.iter().position(|x| x.mag() == 0.) {
+            return Err(Error::NumericalFailure(format!(
+                "particle {} is at the same position as the central body {}",
+                self.state.ids[i], self.central_id
+            )));
+        }
+
+        for dt in fixed_steps(self.state.model_time, time, self.timestep) {
+            self.integrate(dt)?;
+        }
+
+        // the sum may differ from `time` by round-off error
+        self.state.model_time = time;
+
+        return Ok(());
+    }
+}
@@ -0,0 +1,469 @@
+use crate::error::Error;
+use crate::particles::Particle;
+use crate::quantity::systems::{SIUnits, UnitSystem};
+use crate::quantity::typed::{IntoTyped, Length, LengthDimension, MassDimension, TimeDimension, VelocityDimension};
+use crate::quantity::{Units, VectorQuantity};
+use crate::vector::Vector3;
+use std::f64::consts::PI;
+
+/// Largest number of iterations of the solvers of Kepler equation.
+const MAX_ITERATIONS: usize = 50;
+
+/// Order of Laguerre-Conway iterations.
+const LAGUERRE_ORDER: f64 = 5.;
+
+/// Keplerian elements of the relative orbit of two bodies; angles are in radians.
+///
+/// Mean anomaly is `E - e sin(E)` for elliptic orbits, `e sinh(H) - H` for hyperbolic ones and
+/// `D + D^3 / 3` with `D = tan(nu / 2)` (Barker's equation) for parabolic ones; it grows with time
+/// with mean motion `sqrt(G M / |a|^3)` or `sqrt(G M / (2 q^3))` for parabolic orbit.
+///
+/// Elements computed from the state have angles in `[0, 2 pi)` and mean anomaly of elliptic orbit in `[-pi, pi]`.
+/// For equatorial orbits longitude of ascending node is 0 and for circular ones argument of pericenter is 0,
+/// so the position on the orbit is given by the mean anomaly alone.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct OrbitalElements {
+    pericenter: Length,
+    eccentricity: f64,
+    inclination: f64,
+    ascending_node: f64,
+    argument_of_pericenter: f64,
+    mean_anomaly: f64,
+}
+
+impl OrbitalElements {
+    /// Creates elements of elliptic or hyperbolic orbit.
+    ///
+    /// `a`: semi-major axis, positive for elliptic and negative for hyperbolic orbits,
+    /// `e`: eccentricity other than 1 (see `with_pericenter` for parabolic orbits),
+    /// `i`: inclination in `[0, pi]`, `node`: longitude of ascending node, `argument`: argument of pericenter,
+    /// `mean_anomaly`: mean anomaly
+    pub fn new(
+        a: impl IntoTyped<f64, LengthDimension>,
+        e: f64,
+        i: f64,
+        node: f64,
+        argument: f64,
+        mean_anomaly: f64,
+    ) -> Result<OrbitalElements, Error> {
+        let e_check = |e: f64| -> bool { e != 1. };
+        let a_check = |a: f64, e: f64| -> bool { (e < 1. && a > 0.) || (e > 1. && a < 0.) };
+
+        let a = a.into_typed("semi-major axis")?;
+
+        if !e_check(e) {
+            return Err(Error::invalid("eccentricity", e, "parabolic orbit has to be defined by pericenter distance"));
+        }
+        if !a_check(a.value_si(), e) {
+            return Err(Error::invalid(
+                "semi-major axis",
+                a,
+                "must be positive for elliptic and negative for hyperbolic orbits",
+            ));
+        }
+
+        return Self::with_pericenter(Length::from_si(a.value_si() * (1. - e)), e, i, node, argument, mean_anomaly);
+    }
+
+    /// Creates elements of the orbit of any kind, including parabolic one.
+    ///
+    /// `q`: pericenter distance, other parameters are the same as in `new`
+    pub fn with_pericenter(
+        q: impl IntoTyped<f64, LengthDimension>,
+        e: f64,
+        i: f64,
+        node: f64,
+        argument: f64,
+        mean_anomaly: f64,
+    ) -> Result<OrbitalElements, Error> {
+        let q_check = |q: &Length| -> bool { *q > Length::from_si(0.) && q.value_si().is_finite() };
+        let e_check = |e: f64| -> bool { e >= 0. && e.is_finite() };
+        let i_check = |i: f64| -> bool { (0. ..=PI).contains(&i) };
+        let angle_check = |angle: f64| -> bool { angle.is_finite() };
+
+        let q = q.into_typed("pericenter distance")?;
+
+        if !q_check(&q) {
+            return Err(Error::invalid("pericenter distance", q, "must be positive"));
+        }
+        if !e_check(e) {
+            return Err(Error::invalid("eccentricity", e, "must be non-negative"));
+        }
+        if !i_check(i) {
+            return Err(Error::invalid("inclination", i, "must be between 0 and pi"));
+        }
+        if !angle_check(node) {
+            return Err(Error::invalid("longitude of ascending node", node, "must be finite"));
+        }
+        if !angle_check(argument) {
+            return Err(Error::invalid("argument of pericenter", argument, "must be finite"));
+        }
+        if !angle_check(mean_anomaly) {
+            return Err(Error::invalid("mean anomaly", mean_anomaly, "must be finite"));
+        }
+
+        return Ok(OrbitalElements {
+            pericenter: q,
+            eccentricity: e,
+            inclination: i,
+            ascending_node: node,
+            argument_of_pericenter: argument,
+            mean_anomaly,
+        });
+    }
+
+    /// Computes elements of the orbit of the body with relative `position` and `velocity`;
+    /// `mass` is the total mass of both bodies.
+    pub fn from_state(
+        position: impl IntoTyped<Vector3, LengthDimension>,
+        velocity: impl IntoTyped<Vector3, VelocityDimension>,
+        mass: impl IntoTyped<f64, MassDimension>,
+    ) -> Result<OrbitalElements, Error> {
+        let position = position.into_typed("position")?;
+        let velocity = velocity.into_typed("velocity")?;
+        let mu = gravitational_parameter(mass)?;
+
+        let r = position.value_si();
+        let v = velocity.value_si();
+        let h = r.cross(&v);
+
+        if r.mag() == 0. {
+            return Err(Error::invalid("position", position, "must not coincide with the other body"));
+        }
+        if h.mag() == 0. {
+            return Err(Error::invalid("velocity", velocity, "must not be parallel to position"));
+        }
+
+        let e_vector = (r * (v.dot(&v) - mu / r.mag()) - v * r.dot(&v)) / mu;
+        let e = e_vector.mag();
+        let (node, across) = orbital_frame(h);
+
+        let inclination = (h.z / h.mag()).clamp(-1., 1.).acos();
+        let ascending_node = node.y.atan2(node.x).rem_euclid(2. * PI);
+        let argument = e_vector.dot(&across).atan2(e_vector.dot(&node));
+        let true_anomaly = (r.dot(&across).atan2(r.dot(&node)) - argument + PI).rem_euclid(2. * PI) - PI;
+
+        let mean_anomaly = if e < 1. {
+            let (sin_half, cos_half) = (true_anomaly / 2.).sin_cos();
+            let eccentric = 2. * ((1. - e).sqrt() * sin_half).atan2((1. + e).sqrt() * cos_half);
+
+            mean_from_eccentric(e, eccentric)
+        } else if e > 1. {
+            let hyperbolic = 2. * (((e - 1.) / (e + 1.)).sqrt() * (true_anomaly / 2.).tan()).atanh();
+
+            mean_from_eccentric(e, hyperbolic)
+        } else {
+            let d = (true_anomaly / 2.).tan();
+
+            d + d * d * d / 3.
+        };
+
+        return Self::with_pericenter(
+            Length::from_si(h.dot(&h) / mu / (1. + e)),
+            e,
+            inclination,
+            ascending_node,
+            argument.rem_euclid(2. * PI),
+            mean_anomaly,
+        );
+    }
+
+    /// Computes elements of the orbit of `secondary` relative to `primary`.
+    pub fn from_particles(primary: &Particle, secondary: &Particle) -> Result<OrbitalElements, Error> {
+        return Self::from_state(
+            secondary.get_position() - primary.get_position(),
+            secondary.get_velocity() - primary.get_velocity(),
+            primary.get_mass() + secondary.get_mass(),
+        );
+    }
+
+    /// Computes relative position and velocity of the body on the orbit; `mass` is the total mass of both bodies.
+    pub fn to_state(
+        &self,
+        mass: impl IntoTyped<f64, MassDimension>,
+    ) -> Result<(VectorQuantity, VectorQuantity), Error> {
+        let mu = gravitational_parameter(mass)?;
+        let e = self.eccentricity;
+        let q = self.pericenter.value_si();
+        let true_anomaly = true_anomaly(e, self.mean_anomaly)?;
+
+        let (sin_i, cos_i) = self.inclination.sin_cos();
+        let (sin_node, cos_node) = self.ascending_node.sin_cos();
+        let (sin_w, cos_w) = self.argument_of_pericenter.sin_cos();
+        let node = Vector3::new(cos_node, sin_node, 0.);
+        let across = Vector3::new(-sin_node * cos_i, cos_node * cos_i, sin_i);
+        let pericenter = node * cos_w + across * sin_w;
+        let perpendicular = across * cos_w - node * sin_w;
+
+        let p = q * (1. + e);
+        let (sin_nu, cos_nu) = true_anomaly.sin_cos();
+        let r = p / (1. + e * cos_nu);
+        let position = (pericenter * cos_nu + perpendicular * sin_nu) * r;
+        let velocity = (perpendicular * (e + cos_nu) - pericenter * sin_nu) * (mu / p).sqrt();
+
+        return Ok((position * Units::m, velocity * Units::ms));
+    }
+
+    /// Semi-major axis; it is negative for hyperbolic orbits and infinite for parabolic ones.
+    pub fn get_semi_major_axis(&self) -> Length {
+        return Length::from_si(self.pericenter.value_si() / (1. - self.eccentricity));
+    }
+
+    pub fn get_pericenter_distance(&self) -> Length {
+        return self.pericenter;
+    }
+
+    pub fn get_eccentricity(&self) -> f64 {
+        return self.eccentricity;
+    }
+
+    pub fn get_inclination(&self) -> f64 {
+        return self.inclination;
+    }
+
+    pub fn get_longitude_of_ascending_node(&self) -> f64 {
+        return self.ascending_node;
+    }
+
+    pub fn get_argument_of_pericenter(&self) -> f64 {
+        return self.argument_of_pericenter;
+    }
+
+    pub fn get_mean_anomaly(&self) -> f64 {
+        return self.mean_anomaly;
+    }
+}
+
+/// Propagates the body with relative `position` and `velocity` along its two-body orbit by `time`
+/// (possibly negative); `mass` is the total mass of both bodies.
+///
+/// `return`: relative position and velocity after `time`
+pub fn propagate(
+    position: impl IntoTyped<Vector3, LengthDimension>,
+    velocity: impl IntoTyped<Vector3, VelocityDimension>,
+    mass: impl IntoTyped<f64, MassDimension>,
+    time: impl IntoTyped<f64, TimeDimension>,
+) -> Result<(VectorQuantity, VectorQuantity), Error> {
+    let position = position.into_typed("position")?;
+    let velocity = velocity.into_typed("velocity")?;
+    let mu = gravitational_parameter(mass)?;
+    let time = time.into_typed("time")?;
+
+    if position.value_si().mag() == 0. {
+        return Err(Error::invalid("position", position, "must not coincide with the other body"));
+    }
+
+    let (r, v) = advance(position.value_si(), velocity.value_si(), mu, time.value_si())?;
+
+    return Ok((r * Units::m, v * Units::ms));
+}
+
+/// `G * mass` in SI units; `mass` has to be positive.
+fn gravitational_parameter(mass: impl IntoTyped<f64, MassDimension>) -> Result<f64, Error> {
+    let mass_check = |m: f64| -> bool { m > 0. && m.is_finite() };
+
+    let mass = mass.into_typed("mass")?;
+
+    if !mass_check(mass.value_si()) {
+        return Err(Error::invalid("mass", mass, "must be positive"));
+    }
+
+    return Ok(SIUnits.gravitational_constant() * mass.value_si());
+}
+
+/// Unit vectors in the orbital plane with angular momentum `h`: towards the ascending node
+/// (or along x axis for equatorial orbits) and perpendicular to it in the direction of motion.
+fn orbital_frame(h: Vector3) -> (Vector3, Vector3) {
+    let normal = h.unit();
+    let line_of_nodes = Vector3::new(-h.y, h.x, 0.);
+
+    let node = if line_of_nodes.mag() == 0. {
+        Vector3::new(1., 0., 0.)
+    } else {
+        line_of_nodes.unit()
+    };
+
+    return (node, normal.cross(&node));
+}
+
+/// Solves Kepler equation of the orbit with eccentricity `e` for given mean anomaly.
+fn true_anomaly(e: f64, mean_anomaly: f64) -> Result<f64, Error> {
+    if e == 1. {
+        // Barker's equation is a cubic with single real root
+        let w = 1.5 * mean_anomaly;
+        let y = (w + (w * w + 1.).sqrt()).cbrt();
+
+        return Ok(2. * (y - 1. / y).atan());
+    }
+
+    // reduction is skipped when it is not needed as it would lose small anomalies of nearly parabolic orbits
+    let m = if e < 1. && mean_anomaly.abs() > PI {
+        (mean_anomaly + PI).rem_euclid(2. * PI) - PI
+    } else {
+        mean_anomaly
+    };
+
+    // for small anomalies the solution is close to the one of cubic equation
+    let cubic = (6. * m.abs()).cbrt() * m.signum();
+
+    let mut x = if e < 1. {
+        if e < 0.8 {
+            m
+        } else {
+            cubic.abs().min(PI) * m.signum()
+        }
+    } else {
+        cubic.abs().min((2. * m.abs() / e + 1.8).ln()) * m.signum()
+    };
+
+    for _ in 0..MAX_ITERATIONS {
+        let derivative = if e < 1. {
+            (1. - e) + 2. * e * (x / 2.).sin().powi(2)
+        } else {
+            (e - 1.) + 2. * e * (x / 2.).sinh().powi(2)
+        };
+        let delta = (mean_from_eccentric(e, x) - m) / derivative;
+        x -= delta;
+
+        // convergence is quadratic, so after such a small correction the error is at round-off level
+        if delta.abs() <= 1e-14 * x.abs() {
+            return Ok(if e < 1. {
+                2. * ((1. + e).sqrt() * (x / 2.).sin()).atan2((1. - e).sqrt() * (x / 2.).cos())
+            } else {
+                2. * (((e + 1.) / (e - 1.)).sqrt() * (x / 2.).tanh()).atan()
+            });
+        }
+    }
+
+    return Err(Error::NumericalFailure(format!(
+        "Kepler equation did not converge for mean anomaly {} and eccentricity {}",
+        mean_anomaly, e
+    )));
+}
+
+/// Mean anomaly of the elliptic (hyperbolic) orbit with eccentricity `e` for eccentric (hyperbolic) anomaly `x`.
+///
+/// It is written as `|1 - e| x + e (x - sin(x))` to keep precision for nearly parabolic orbits.
+fn mean_from_eccentric(e: f64, x: f64) -> f64 {
+    if e < 1. {
+        return (1. - e) * x + e * sine_remainder(x, -1.);
+    }
+
+    return (e - 1.) * x + e * sine_remainder(x, 1.);
+}
+
+/// `x - sin(x)` if `sign` is -1 and `sinh(x) - x` if it is 1, without cancellation for small `x`.
+fn sine_remainder(x: f64, sign: f64) -> f64 {
+    if x.abs() > 1. {
+        return if sign < 0. { x - x.sin() } else { x.sinh() - x };
+    }
+
+    let mut sum = 0.;
+    let mut term = x * x * x / 6.;
+
+    for k in 1..12 {
+        sum += term;
+        term *= sign * x * x / (((2 * k + 2) * (2 * k + 3)) as f64);
+    }
+
+    return sum;
+}
+
+/// Stumpff functions `c2(z)` and `c3(z)`.
+fn stumpff(z: f64) -> (f64, f64) {
+    if z.abs() < 0.1 {
+        // series converge fast for small arguments and avoid cancellation in closed forms
+        let mut c2 = 0.;
+        let mut c3 = 0.;
+        let mut term2 = 0.5;
+        let mut term3 = 1. / 6.;
+
+        for k in 0..8 {
+            c2 += term2;
+            c3 += term3;
+            term2 *= -z / (((2 * k + 3) * (2 * k + 4)) as f64);
+            term3 *= -z / (((2 * k + 4) * (2 * k + 5)) as f64);
+        }
+
+        return (c2, c3);
+    }
+
+    if z > 0. {
+        let s = z.sqrt();
+
+        return ((1. - s.cos()) / z, (s - s.sin()) / (z * s));
+    }
+
+    let s = (-z).sqrt();
+
+    return ((1. - s.cosh()) / z, (s.sinh() - s) / (-z * s));
+}
+
+/// Advances the relative two-body orbit with gravitational parameter `mu = G (m1 + m2)` from position `r0`
+/// and velocity `v0` by time `dt` (possibly negative) using universal variable formulation.
+/// Elliptic, parabolic and hyperbolic orbits are handled in the same way; values may be in any consistent units.
+///
+/// `return`: position and velocity after `dt`
+pub(crate) fn advance(r0: Vector3, v0: Vector3, mu: f64, dt: f64) -> Result<(Vector3, Vector3), Error> {
+    let r0_mag = r0.mag();
+    let sqrt_mu = mu.sqrt();
+    let sigma0 = r0.dot(&v0) / sqrt_mu;
+    // inverse of semi-major axis: positive for elliptic and negative for hyperbolic orbits
+    let alpha = 2. / r0_mag - v0.dot(&v0) / mu;
+    let mut dt = dt;
+
+    if alpha > 0. {
+        let period = 2. * PI / (sqrt_mu * alpha * alpha.sqrt());
+        dt -= (dt / period).round() * period;
+    }
+
+    let kepler = |x: f64| -> (f64, f64, f64) {
+        let z = alpha * x * x;
+        let (c2, c3) = stumpff(z);
+        let f = sigma0 * x * x * c2 + (1. - alpha * r0_mag) * x * x * x * c3 + r0_mag * x - sqrt_mu * dt;
+        let df = sigma0 * x * (1. - z * c3) + (1. - alpha * r0_mag) * x * x * c2 + r0_mag;
+        let ddf = sigma0 * (1. - z * c2) + (1. - alpha * r0_mag) * x * (1. - z * c3);
+
+        return (f, df, ddf);
+    };
+
+    let mut x = if alpha > 0. {
+        sqrt_mu * alpha * dt
+    } else {
+        sqrt_mu * dt / r0_mag
+    };
+    let mut converged = dt == 0.;
+
+    for _ in 0..MAX_ITERATIONS {
+        if converged {
+            break;
+        }
+
+        let (f, df, ddf) = kepler(x);
+        let n = LAGUERRE_ORDER;
+        let root = ((n - 1.) * (n - 1.) * df * df - n * (n - 1.) * f * ddf).abs().sqrt();
+        let delta = n * f / (df + df.signum() * root);
+
+        x -= delta;
+        // convergence is cubic, so after such a small correction the error is at round-off level
+        converged = delta.abs() <= 1e-14 * x.abs();
+    }
+
+    if !converged || !x.is_finite() {
+        return Err(Error::NumericalFailure(format!(
+            "Kepler equation did not converge for time {} and inverse semi-major axis {}",
+            dt, alpha
+        )));
+    }
+
+    let z = alpha * x * x;
+    let (c2, c3) = stumpff(z);
+    let f = 1. - x * x / r0_mag * c2;
+    let g = dt - x * x * x / sqrt_mu * c3;
+    let r = f * r0 + g * v0;
+    let r_mag = r.mag();
+    let df = sqrt_mu / (r_mag * r0_mag) * x * (z * c3 - 1.);
+    let dg = 1. - x * x / r_mag * c2;
+
+    return Ok((r, df * r0 + dg * v0));
+}
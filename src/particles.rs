@@ -1,19 +1,29 @@
+use crate::error::Error;
+use crate::integrators::direct::DirectSum;
+use crate::quantity::systems::{SIUnits, UnitSystem};
+use crate::quantity::typed::{IntoTyped, LengthDimension, Mass, MassDimension, VelocityDimension};
 use crate::quantity::*;
+use crate::softening::Softening;
 use crate::vector::Vector3;
 use std::cmp::PartialEq;
 
+/// Material point. Each particle gets identifier that is unique within `ParticleSet` when it is added to the set;
+/// integrators preserve it, so that the same particle can be found in different states of the system.
 pub struct Particle {
+    id: u64,
     position: VectorQuantity,
     velocity: VectorQuantity,
     mass: ScalarQuantity,
 }
 
 impl Particle {
+    /// Creates new particle; arguments are either typed quantities or dynamic ones
+    /// equivalent to `Units::m`, `Units::ms` and `Units::kg` respectively.
     pub fn new(
-        position: VectorQuantity,
-        velocity: VectorQuantity,
-        mass: ScalarQuantity,
-    ) -> Result<Particle, &'static str> {
+        position: impl IntoTyped<Vector3, LengthDimension>,
+        velocity: impl IntoTyped<Vector3, VelocityDimension>,
+        mass: impl IntoTyped<f64, MassDimension>,
+    ) -> Result<Particle, Error> {
         let mut res = Particle::empty();
 
         res.set_position(position)?;
@@ -25,58 +35,56 @@ impl Particle {
 
     pub fn empty() -> Particle {
         return Particle {
+            id: 0,
             position: Vector3::null_vector() * Units::m,
             velocity: Vector3::null_vector() * Units::ms,
             mass: 1. * Units::kg,
         };
     }
 
-    pub fn set_position(&mut self, position: VectorQuantity) -> Result<(), &'static str> {
-        let check_pos = |pos: &VectorQuantity| -> bool {
-            return pos.is_compatible(Units::m.convert());
-        };
-
-        if !check_pos(&position) {
-            return Err("incorrect position");
-        }
-
-        self.position = position;
+    pub fn set_position(
+        &mut self,
+        position: impl IntoTyped<Vector3, LengthDimension>,
+    ) -> Result<(), Error> {
+        self.position = position.into_typed("position")?.get_quantity();
 
         return Ok(());
     }
 
-    pub fn set_velocity(&mut self, velocity: VectorQuantity) -> Result<(), &'static str> {
-        let check_vel = |vel: &VectorQuantity| -> bool {
-            return vel.is_compatible(Units::ms.convert());
-        };
-
-        if !check_vel(&velocity) {
-            return Err("incorrect velocity");
-        }
-
-        self.velocity = velocity;
+    pub fn set_velocity(
+        &mut self,
+        velocity: impl IntoTyped<Vector3, VelocityDimension>,
+    ) -> Result<(), Error> {
+        self.velocity = velocity.into_typed("velocity")?.get_quantity();
 
         return Ok(());
     }
 
-    pub fn set_mass(&mut self, mass: ScalarQuantity) -> Result<(), &'static str> {
-        let check_mass = |m: &ScalarQuantity| -> bool {
-            if m.is_compatible(Units::kg.convert()) {
-                return *m >= 0. * Units::kg;
-            }
-
-            return false;
+    pub fn set_mass(&mut self, mass: impl IntoTyped<f64, MassDimension>) -> Result<(), Error> {
+        let check_mass = |m: &Mass| -> bool {
+            return *m >= Mass::from_si(0.);
         };
 
+        let mass = mass.into_typed("mass")?;
+
         if !check_mass(&mass) {
-            return Err("incorrect mass");
+            return Err(Error::invalid("mass", mass, "must be non-negative"));
         }
 
-        self.mass = mass;
+        self.mass = mass.get_quantity();
 
         return Ok(());
     }
 
+    /// Overrides identifier of the particle; it is up to the caller to keep identifiers unique.
+    pub fn set_id(&mut self, id: u64) {
+        self.id = id;
+    }
+
+    pub fn get_id(&self) -> u64 {
+        return self.id;
+    }
+
     pub fn get_position(&self) -> VectorQuantity {
         return self.position;
     }
@@ -102,26 +110,40 @@ impl PartialEq for Particle {
 /// to be able to calculate things like energies and so on
 pub struct ParticleSet {
     pub particles: Vec<Particle>,
+    next_id: u64,
 }
 
 impl ParticleSet {
     /// Creates new instance of a `ParticleSet`
     ///
     /// `return`: `ParticleSet` or the error with description
-    pub fn new() -> Result<ParticleSet, &'static str> {
+    pub fn new() -> Result<ParticleSet, Error> {
         return Ok(ParticleSet {
             particles: Vec::new(),
+            next_id: 0,
         });
     }
 
-    /// Adds one particle to the set
+    /// Adds one particle to the set and gives it the next identifier of the set,
+    /// so particles of the set created by generator have identifiers from 0 to N - 1
     ///
     /// `p`: given particle
-    pub fn add_particle(&mut self, p: Particle) {
+    pub fn add_particle(&mut self, mut p: Particle) {
+        p.id = self.next_id;
+        self.next_id += 1;
         self.particles.push(p);
     }
 
-    /// Adds one set of particles into another
+    /// Adds one particle to the set keeping its identifier, e.g. to restore the state of the system;
+    /// it is up to the caller to keep identifiers unique
+    ///
+    /// `p`: given particle
+    pub fn restore_particle(&mut self, p: Particle) {
+        self.next_id = self.next_id.max(p.id + 1);
+        self.particles.push(p);
+    }
+
+    /// Adds one set of particles into another; added particles get new identifiers
     ///
     /// `ps`: given set of particles
     pub fn add_particles(&mut self, ps: ParticleSet) {
@@ -130,6 +152,82 @@ impl ParticleSet {
         }
     }
 
+    /// Returns particle with given identifier;
+    /// Complexity: O(N)
+    pub fn get_by_id(&self, id: u64) -> Option<&Particle> {
+        return self.particles.iter().find(|p| p.get_id() == id);
+    }
+
+    /// Returns mutable reference to the particle with given identifier;
+    /// Complexity: O(N)
+    pub fn get_by_id_mut(&mut self, id: u64) -> Option<&mut Particle> {
+        return self.particles.iter_mut().find(|p| p.get_id() == id);
+    }
+
+    /// Returns total mass of the particle set;
+    /// Complexity: O(N)
+    /// `return`: ScalarQuantity equivalent to Units::kg
+    pub fn get_total_mass(&self) -> ScalarQuantity {
+        let mut result = 0. * Units::kg;
+
+        for p in self.particles.iter() {
+            result += p.get_mass();
+        }
+
+        return result;
+    }
+
+    /// Returns position and velocity of the centre of mass of the particle set;
+    /// Complexity: O(N)
+    /// `return`: VectorQuantity equivalent to Units::m and Units::ms or error if the set is empty or massless
+    pub fn get_center_of_mass(&self) -> Result<(VectorQuantity, VectorQuantity), Error> {
+        if self.particles.is_empty() {
+            return Err(Error::EmptySet);
+        }
+
+        let mass_check = |m: &Mass| -> bool { *m > Mass::from_si(0.) };
+        let mass = self.get_total_mass().into_typed("total mass")?;
+
+        if !mass_check(&mass) {
+            return Err(Error::invalid("total mass", mass, "must be positive"));
+        }
+
+        let mut position = Vector3::null_vector() * Units::m * Units::kg;
+        let mut velocity = Vector3::null_vector() * Units::ms * Units::kg;
+
+        for p in self.particles.iter() {
+            position += p.get_position() * p.get_mass();
+            velocity += p.get_velocity() * p.get_mass();
+        }
+
+        return Ok((position / mass.get_quantity(), velocity / mass.get_quantity()));
+    }
+
+    /// Moves all particles by `position` and adds `velocity` to their velocities;
+    /// arguments are either typed quantities or dynamic ones equivalent to `Units::m` and `Units::ms`.
+    pub fn shift(
+        &mut self,
+        position: impl IntoTyped<Vector3, LengthDimension>,
+        velocity: impl IntoTyped<Vector3, VelocityDimension>,
+    ) -> Result<(), Error> {
+        let position = position.into_typed("position")?.get_quantity();
+        let velocity = velocity.into_typed("velocity")?.get_quantity();
+
+        for p in self.particles.iter_mut() {
+            p.position += position;
+            p.velocity += velocity;
+        }
+
+        return Ok(());
+    }
+
+    /// Moves the set so that its centre of mass is at rest at the origin.
+    pub fn move_to_center_of_mass(&mut self) -> Result<(), Error> {
+        let (position, velocity) = self.get_center_of_mass()?;
+
+        return self.shift(position * -1., velocity * -1.);
+    }
+
     /// Returns kinetic energy of the particle set;
     /// Complexity: O(N)
     /// `return`: ScalarQuantity equivalent to Units::J
@@ -147,20 +245,30 @@ impl ParticleSet {
     /// Complexity: O(N^2)
     /// `return`: ScalarQuantity equivalent to Units::J
     pub fn get_potential_energy(&self) -> ScalarQuantity {
-        let mut result = 0. * Units::J;
-        let set = &self.particles;
-
-        for i in 0..set.len() {
-            for j in 0..set.len() {
-                if i == j {
-                    continue;
-                }
+        return self.get_softened_potential_energy(&Softening::none());
+    }
 
-                let r = (set[i].get_position() - set[j].get_position()).mag();
-                result -= Units::G * set[i].get_mass() * set[j].get_mass() / r;
-            }
-        }
+    /// Returns potential energy of the particle set with gravitational interaction smoothed by `softening`;
+    /// Complexity: O(N^2)
+    /// `return`: ScalarQuantity equivalent to Units::J
+    pub fn get_softened_potential_energy(&self, softening: &Softening) -> ScalarQuantity {
+        let positions: Vec<Vector3> = self
+            .particles
+            .iter()
+            .map(|p| -> Vector3 { p.get_position().value_in(Units::m) })
+            .collect();
+        let masses: Vec<f64> = self
+            .particles
+            .iter()
+            .map(|p| -> f64 { p.get_mass().value_in(Units::kg) })
+            .collect();
+        let sum = DirectSum {
+            masses: &masses,
+            G: SIUnits.gravitational_constant(),
+            kernel: softening.get_kernel(),
+            eps: softening.get_length().value_in(Units::m),
+        };
 
-        return result;
+        return sum.potential_energy(&positions) * Units::J;
     }
 }
@@ -1,3 +1,5 @@
+use crate::error::Error;
+use crate::quantity::systems::UnitSystem;
 use crate::vector::Vector3;
 use std::cmp::{Ordering, PartialEq, PartialOrd};
 use std::fmt::{Display, Formatter, Result, LowerExp};
@@ -104,6 +106,7 @@ impl Mul<SI> for f64 {
 #[allow(non_camel_case_types)]
 pub enum Units {
     m,      // meter
+    cm,     // centimeter
     AU,     // astronomical unit
     pc,     // parsec
     kpc,    // kiloparsec
@@ -116,6 +119,7 @@ pub enum Units {
     yr,     // year
     Myr,    // megayear
     kg,     // kilogram
+    g,      // gram
     MEarth, // mass of the earth
     MSun,   // mass of the sun
     ms,     // meters per second
@@ -128,6 +132,7 @@ impl Units {
     pub fn convert(&self) -> ScalarQuantity {
         match self {
             Self::m => 1.0 * SI::new(1., 0., 0.),
+            Self::cm => 1e-2 * SI::new(1., 0., 0.),
             Self::AU => 1.496e+11 * SI::new(1., 0., 0.),
             Self::pc => 3.086e+16 * SI::new(1., 0., 0.),
             Self::kpc => 3.086e+19 * SI::new(1., 0., 0.),
@@ -140,6 +145,7 @@ impl Units {
             Self::yr => 365.0 * 86400.0 * SI::new(0., 1., 0.),
             Self::Myr => 1e+6 * 365.0 * 86400.0 * SI::new(0., 1., 0.),
             Self::kg => 1.0 * SI::new(0., 0., 1.),
+            Self::g => 1e-3 * SI::new(0., 0., 1.),
             Self::MEarth => 5.972e+24 * SI::new(0., 0., 1.),
             Self::MSun => 1.989e+30 * SI::new(0., 0., 1.),
             Self::ms => 1. * SI::new(1., -1., 0.),
@@ -238,9 +244,78 @@ impl<T: QuantityBound> Quantity<T>{
         return self.units == q.units;
     }
 
+    /// Checks that quantity has the same dimension as `expected`.
+    ///
+    /// `name`: name of the quantity that is reported in the error
+    pub fn check_compatibility(&self, name: &'static str, expected: ScalarQuantity) -> std::result::Result<(), Error> {
+        if !self.is_compatible(expected) {
+            return Err(Error::IncompatibleUnits {
+                name,
+                quantity: self.to_string(),
+                expected: expected.units.to_string(),
+            });
+        }
+
+        return Ok(());
+    }
+
     pub fn value_in(&self, unit: Units) -> T {
         return self.value_in_q(unit.convert());
     }
+
+    /// Same as `value_in_q` but returns error instead of panic if units are incompatible.
+    pub fn try_value_in_q(&self, q: Quantity<f64>) -> std::result::Result<T, Error> {
+        self.check_compatibility("quantity", q)?;
+
+        return Ok(self.value / q.value);
+    }
+
+    /// Same as `value_in` but returns error instead of panic if units are incompatible.
+    pub fn try_value_in(&self, unit: Units) -> std::result::Result<T, Error> {
+        return self.try_value_in_q(unit.convert());
+    }
+
+    /// Same as `+` but returns error instead of panic if units are incompatible.
+    pub fn checked_add(self, rhs: Quantity<T>) -> std::result::Result<Quantity<T>, Error> {
+        rhs.check_compatibility("right-hand side", self.unit())?;
+
+        return Ok(Quantity {
+            value: self.value + rhs.value,
+            units: self.units
+        });
+    }
+
+    /// Same as `-` but returns error instead of panic if units are incompatible.
+    pub fn checked_sub(self, rhs: Quantity<T>) -> std::result::Result<Quantity<T>, Error> {
+        rhs.check_compatibility("right-hand side", self.unit())?;
+
+        return Ok(Quantity {
+            value: self.value - rhs.value,
+            units: self.units
+        });
+    }
+
+    /// Value of the quantity in units of given unit system.
+    pub fn value_in_system(&self, system: &dyn UnitSystem) -> T {
+        return self.value_in_q(system.unit_like(self.unit()));
+    }
+
+    /// Quantity equal to one unit of the same dimension.
+    fn unit(&self) -> ScalarQuantity {
+        return ScalarQuantity {
+            value: 1.,
+            units: self.units
+        };
+    }
+}
+
+impl<T: QuantityBound + PartialOrd> Quantity<T> {
+    /// Same as `partial_cmp` but returns error instead of panic if units are incompatible.
+    pub fn checked_cmp(&self, rhs: &Quantity<T>) -> std::result::Result<Option<Ordering>, Error> {
+        rhs.check_compatibility("right-hand side", self.unit())?;
+
+        return Ok(self.value.partial_cmp(&rhs.value));
+    }
 }
 
 impl<T: QuantityBound> Display for Quantity<T> {
@@ -515,3 +590,8 @@ impl VectorQuantity {
         };
     }
 }
+
+/// This module contains `UnitSystem` trait and its implementations: SI, CGS, astrophysical and N-body units.
+pub mod systems;
+/// This module contains quantities whose dimension is checked at compile time.
+pub mod typed;
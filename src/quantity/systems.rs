@@ -0,0 +1,174 @@
+use super::{ScalarQuantity, Units};
+use crate::error::Error;
+use crate::particles::ParticleSet;
+use crate::quantity::typed::{IntoTyped, Length, LengthDimension, Mass, MassDimension};
+
+/// System of units defined by its units of length, time and mass.
+pub trait UnitSystem: Send + Sync {
+    /// Unit of length.
+    fn length(&self) -> ScalarQuantity;
+    /// Unit of time.
+    fn time(&self) -> ScalarQuantity;
+    /// Unit of mass.
+    fn mass(&self) -> ScalarQuantity;
+
+    /// Unit of velocity.
+    fn velocity(&self) -> ScalarQuantity {
+        return self.length() / self.time();
+    }
+
+    /// Unit of acceleration.
+    fn acceleration(&self) -> ScalarQuantity {
+        return self.velocity() / self.time();
+    }
+
+    /// Unit of energy.
+    fn energy(&self) -> ScalarQuantity {
+        return self.mass() * self.velocity().pow(2.);
+    }
+
+    /// Unit of this system that has the same dimension as `q`.
+    fn unit_like(&self, q: ScalarQuantity) -> ScalarQuantity {
+        return self.length().pow(q.units.meters)
+            * self.time().pow(q.units.seconds)
+            * self.mass().pow(q.units.kilograms);
+    }
+
+    /// Gravitational constant in units of this system.
+    fn gravitational_constant(&self) -> f64 {
+        let g = Units::G.convert();
+
+        return g.value_in_q(self.unit_like(g));
+    }
+}
+
+/// International system of units: meter, second, kilogram.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct SIUnits;
+
+impl UnitSystem for SIUnits {
+    fn length(&self) -> ScalarQuantity {
+        return Units::m.convert();
+    }
+
+    fn time(&self) -> ScalarQuantity {
+        return Units::s.convert();
+    }
+
+    fn mass(&self) -> ScalarQuantity {
+        return Units::kg.convert();
+    }
+}
+
+/// Centimeter, second, gram.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct CGSUnits;
+
+impl UnitSystem for CGSUnits {
+    fn length(&self) -> ScalarQuantity {
+        return Units::cm.convert();
+    }
+
+    fn time(&self) -> ScalarQuantity {
+        return Units::s.convert();
+    }
+
+    fn mass(&self) -> ScalarQuantity {
+        return Units::g.convert();
+    }
+}
+
+/// Astrophysical units: parsec, megayear, mass of the Sun.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct AstroUnits;
+
+impl UnitSystem for AstroUnits {
+    fn length(&self) -> ScalarQuantity {
+        return Units::pc.convert();
+    }
+
+    fn time(&self) -> ScalarQuantity {
+        return Units::Myr.convert();
+    }
+
+    fn mass(&self) -> ScalarQuantity {
+        return Units::MSun.convert();
+    }
+}
+
+/// Standard Hénon N-body units: `G = 1`, total mass `M = 1` and virial radius `R = 1`,
+/// so that total energy of the system in virial equilibrium is `E = -1/4`.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct NBodyUnits {
+    mass: ScalarQuantity,
+    length: ScalarQuantity,
+    time: ScalarQuantity,
+}
+
+impl NBodyUnits {
+    /// Creates N-body units from total mass and virial radius `R = -G M^2 / (2 W)` of the system,
+    /// where `W` is its potential energy.
+    pub fn new(
+        total_mass: impl IntoTyped<f64, MassDimension>,
+        virial_radius: impl IntoTyped<f64, LengthDimension>,
+    ) -> Result<NBodyUnits, Error> {
+        let mass_check = |m: &Mass| -> bool { *m > Mass::from_si(0.) };
+        let radius_check = |r: &Length| -> bool { *r > Length::from_si(0.) };
+
+        let total_mass = total_mass.into_typed("total mass")?;
+        let virial_radius = virial_radius.into_typed("virial radius")?;
+
+        if !mass_check(&total_mass) {
+            return Err(Error::invalid("total mass", total_mass, "must be positive"));
+        }
+        if !radius_check(&virial_radius) {
+            return Err(Error::invalid("virial radius", virial_radius, "must be positive"));
+        }
+
+        let mass = total_mass.get_quantity();
+        let length = virial_radius.get_quantity();
+        let time = (length.pow(3.) / (Units::G * mass)).pow(0.5);
+
+        return Ok(NBodyUnits { mass, length, time });
+    }
+
+    /// Creates N-body units from total mass and potential energy of `particle_set`.
+    /// The potential energy must be negative, so the set needs at least two particles with positive masses;
+    /// for a single particle the virial radius is infinite.
+    pub fn from_particle_set(particle_set: &ParticleSet) -> Result<NBodyUnits, Error> {
+        if particle_set.particles.is_empty() {
+            return Err(Error::EmptySet);
+        }
+
+        let energy_check = |w: &ScalarQuantity| -> bool { w.value_in(Units::J) < 0. };
+
+        let total_mass = particle_set.get_total_mass();
+        let potential_energy = particle_set.get_potential_energy();
+
+        if !energy_check(&potential_energy) {
+            return Err(Error::invalid(
+                "potential energy",
+                potential_energy,
+                "must be negative, which needs at least two massive particles",
+            ));
+        }
+
+        let virial_radius = -1. * Units::G * total_mass.pow(2.) / (2. * potential_energy);
+
+        return Self::new(total_mass, virial_radius);
+    }
+}
+
+impl UnitSystem for NBodyUnits {
+    fn length(&self) -> ScalarQuantity {
+        return self.length;
+    }
+
+    fn time(&self) -> ScalarQuantity {
+        return self.time;
+    }
+
+    fn mass(&self) -> ScalarQuantity {
+        return self.mass;
+    }
+}
@@ -0,0 +1,273 @@
+//! Typed layer over `Quantity`: dimension is a type parameter, so passing, for example, mass
+//! where length is expected is a compile-time error.
+//!
+//! ```compile_fail
+//! use xbody_model::quantity::typed::{Length, Mass};
+//! use xbody_model::quantity::Units;
+//!
+//! let m = Mass::new(1. * Units::MSun).unwrap();
+//! let r: Length = m;
+//! ```
+//!
+//! Functions that accept `impl IntoTyped` take both typed and dynamic quantities, but typed ones of a wrong dimension are rejected by compiler:
+//!
+//! ```compile_fail
+//! use xbody_model::generators::plummer::Plummer;
+//! use xbody_model::quantity::typed::Mass;
+//! use xbody_model::quantity::Units;
+//!
+//! let m = Mass::new(1. * Units::MSun).unwrap();
+//! let plummer = Plummer::new(m, 100, m);
+//! ```
+//!
+//! Dynamic quantities are converted into typed ones with a single runtime check:
+//!
+//! ```
+//! use xbody_model::quantity::typed::Length;
+//! use xbody_model::quantity::{ScalarQuantity, Units};
+//!
+//! let r = Length::new(2. * Units::pc).unwrap();
+//! let q: ScalarQuantity = r.into();
+//!
+//! assert!(Length::new(2. * Units::s).is_err());
+//! ```
+
+use super::{Quantity, QuantityBound, ScalarQuantity, Units};
+use crate::error::Error;
+use crate::vector::Vector3;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt::{Debug, Display, Formatter, Result};
+use std::marker::PhantomData;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+/// Physical dimension that is known at compile time.
+pub trait Dimension: PartialEq + Debug + Clone + Copy {
+    /// Name of the dimension that is reported in errors.
+    const NAME: &'static str;
+
+    /// One SI unit of the dimension.
+    fn unit() -> ScalarQuantity;
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct LengthDimension;
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct TimeDimension;
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct MassDimension;
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct VelocityDimension;
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct EnergyDimension;
+
+impl Dimension for LengthDimension {
+    const NAME: &'static str = "length";
+
+    fn unit() -> ScalarQuantity {
+        return Units::m.convert();
+    }
+}
+
+impl Dimension for TimeDimension {
+    const NAME: &'static str = "time";
+
+    fn unit() -> ScalarQuantity {
+        return Units::s.convert();
+    }
+}
+
+impl Dimension for MassDimension {
+    const NAME: &'static str = "mass";
+
+    fn unit() -> ScalarQuantity {
+        return Units::kg.convert();
+    }
+}
+
+impl Dimension for VelocityDimension {
+    const NAME: &'static str = "velocity";
+
+    fn unit() -> ScalarQuantity {
+        return Units::ms.convert();
+    }
+}
+
+impl Dimension for EnergyDimension {
+    const NAME: &'static str = "energy";
+
+    fn unit() -> ScalarQuantity {
+        return Units::J.convert();
+    }
+}
+
+/// `Quantity` whose dimension `D` is checked at compile time.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Typed<T: QuantityBound, D: Dimension> {
+    quantity: Quantity<T>,
+    dimension: PhantomData<D>,
+}
+
+pub type Length = Typed<f64, LengthDimension>;
+pub type Time = Typed<f64, TimeDimension>;
+pub type Mass = Typed<f64, MassDimension>;
+pub type Velocity = Typed<f64, VelocityDimension>;
+pub type Energy = Typed<f64, EnergyDimension>;
+pub type LengthVector = Typed<Vector3, LengthDimension>;
+pub type VelocityVector = Typed<Vector3, VelocityDimension>;
+
+impl<T: QuantityBound, D: Dimension> Typed<T, D> {
+    /// Creates typed quantity from dynamic one; returns error if `quantity` has dimension other than `D`.
+    pub fn new(quantity: Quantity<T>) -> std::result::Result<Typed<T, D>, Error> {
+        quantity.check_compatibility(D::NAME, D::unit())?;
+
+        return Ok(Typed {
+            quantity,
+            dimension: PhantomData,
+        });
+    }
+
+    /// Creates typed quantity from its value in SI units; this can not fail.
+    pub fn from_si(value: T) -> Typed<T, D> {
+        return Typed {
+            quantity: Quantity {
+                value,
+                units: D::unit().units,
+            },
+            dimension: PhantomData,
+        };
+    }
+
+    /// Value of the quantity in SI units.
+    pub fn value_si(&self) -> T {
+        return self.quantity.value;
+    }
+
+    /// Value of the quantity in given units; returns error if `unit` has dimension other than `D`.
+    pub fn value_in(&self, unit: Units) -> std::result::Result<T, Error> {
+        return self.quantity.try_value_in(unit);
+    }
+
+    /// Returns dynamic quantity.
+    pub fn get_quantity(&self) -> Quantity<T> {
+        return self.quantity;
+    }
+}
+
+impl<D: Dimension> Typed<Vector3, D> {
+    pub fn mag(&self) -> Typed<f64, D> {
+        return Typed::from_si(self.quantity.value.mag());
+    }
+}
+
+/// Values that can be converted into `Typed<T, D>`.
+///
+/// Typed quantities are converted without any checks, so the dimension mismatch is detected by compiler;
+/// dynamic `Quantity<T>` is checked at runtime.
+pub trait IntoTyped<T: QuantityBound, D: Dimension> {
+    /// `name`: name of the value that is reported in the error
+    fn into_typed(self, name: &'static str) -> std::result::Result<Typed<T, D>, Error>;
+}
+
+impl<T: QuantityBound, D: Dimension> IntoTyped<T, D> for Typed<T, D> {
+    fn into_typed(self, _name: &'static str) -> std::result::Result<Typed<T, D>, Error> {
+        return Ok(self);
+    }
+}
+
+impl<T: QuantityBound, D: Dimension> IntoTyped<T, D> for Quantity<T> {
+    fn into_typed(self, name: &'static str) -> std::result::Result<Typed<T, D>, Error> {
+        self.check_compatibility(name, D::unit())?;
+
+        return Ok(Typed {
+            quantity: self,
+            dimension: PhantomData,
+        });
+    }
+}
+
+impl<T: QuantityBound, D: Dimension> From<Typed<T, D>> for Quantity<T> {
+    fn from(typed: Typed<T, D>) -> Quantity<T> {
+        return typed.quantity;
+    }
+}
+
+impl<T: QuantityBound, D: Dimension> TryFrom<Quantity<T>> for Typed<T, D> {
+    type Error = Error;
+
+    fn try_from(quantity: Quantity<T>) -> std::result::Result<Typed<T, D>, Error> {
+        return Typed::new(quantity);
+    }
+}
+
+impl<T: QuantityBound, D: Dimension> Display for Typed<T, D> {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        return write!(f, "{}", self.quantity);
+    }
+}
+
+impl<T: QuantityBound, D: Dimension> Add for Typed<T, D> {
+    type Output = Typed<T, D>;
+
+    fn add(self, rhs: Typed<T, D>) -> Typed<T, D> {
+        return Typed::from_si(self.quantity.value + rhs.quantity.value);
+    }
+}
+
+impl<T: QuantityBound, D: Dimension> AddAssign for Typed<T, D> {
+    fn add_assign(&mut self, rhs: Typed<T, D>) {
+        self.quantity.value += rhs.quantity.value;
+    }
+}
+
+impl<T: QuantityBound, D: Dimension> Sub for Typed<T, D> {
+    type Output = Typed<T, D>;
+
+    fn sub(self, rhs: Typed<T, D>) -> Typed<T, D> {
+        return Typed::from_si(self.quantity.value - rhs.quantity.value);
+    }
+}
+
+impl<T: QuantityBound, D: Dimension> SubAssign for Typed<T, D> {
+    fn sub_assign(&mut self, rhs: Typed<T, D>) {
+        self.quantity.value -= rhs.quantity.value;
+    }
+}
+
+impl<T: QuantityBound, D: Dimension> Neg for Typed<T, D> {
+    type Output = Typed<T, D>;
+
+    fn neg(self) -> Typed<T, D> {
+        return Typed::from_si(self.quantity.value * -1.);
+    }
+}
+
+impl<T: QuantityBound, D: Dimension> Mul<f64> for Typed<T, D> {
+    type Output = Typed<T, D>;
+
+    fn mul(self, rhs: f64) -> Typed<T, D> {
+        return Typed::from_si(self.quantity.value * rhs);
+    }
+}
+
+impl<T: QuantityBound, D: Dimension> Mul<Typed<T, D>> for f64 {
+    type Output = Typed<T, D>;
+
+    fn mul(self, rhs: Typed<T, D>) -> Typed<T, D> {
+        return rhs * self;
+    }
+}
+
+impl<T: QuantityBound, D: Dimension> Div<f64> for Typed<T, D> {
+    type Output = Typed<T, D>;
+
+    fn div(self, rhs: f64) -> Typed<T, D> {
+        return Typed::from_si(self.quantity.value / rhs);
+    }
+}
+
+impl<D: Dimension> PartialOrd for Typed<f64, D> {
+    fn partial_cmp(&self, rhs: &Typed<f64, D>) -> Option<Ordering> {
+        return self.quantity.value.partial_cmp(&rhs.quantity.value);
+    }
+}
@@ -0,0 +1,163 @@
+use crate::error::Error;
+use crate::quantity::{ScalarQuantity, Units};
+use std::cmp::Ordering;
+
+/// Kernel that is used to smooth gravitational interaction of two particles on small distances.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum SofteningKernel {
+    /// Pure Newtonian interaction, softening length is ignored.
+    None,
+    /// Plummer kernel: `1 / r` is replaced with `1 / sqrt(r^2 + eps^2)`.
+    Plummer,
+    /// Cubic spline kernel of Monaghan & Lattanzio (1985) in the form used by GADGET.
+    /// Interaction is exactly Newtonian beyond `2.8 * eps`; `eps` is the equivalent Plummer softening length.
+    Spline,
+}
+
+/// Represents the softening of gravitational interaction: kernel and its softening length.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Softening {
+    kernel: SofteningKernel,
+    length: ScalarQuantity,
+}
+
+impl Softening {
+    /// Creates new softening with given kernel and softening length.
+    ///
+    /// `length`: `ScalarQuantity` equivalent to `Units::m`, must be non-negative
+    pub fn new(kernel: SofteningKernel, length: ScalarQuantity) -> Result<Softening, Error> {
+        let length_check = |l: &ScalarQuantity| -> Result<bool, Error> {
+            return Ok(matches!(
+                l.checked_cmp(&(0. * Units::m))?,
+                Some(Ordering::Greater) | Some(Ordering::Equal)
+            ));
+        };
+
+        length.check_compatibility("softening length", Units::m.convert())?;
+
+        if !length_check(&length)? {
+            return Err(Error::invalid("softening length", length, "must be non-negative"));
+        }
+
+        return Ok(Softening { kernel, length });
+    }
+
+    /// Softening that leaves interaction purely Newtonian.
+    pub fn none() -> Softening {
+        return Softening {
+            kernel: SofteningKernel::None,
+            length: 0. * Units::m,
+        };
+    }
+
+    pub fn get_kernel(&self) -> SofteningKernel {
+        return self.kernel;
+    }
+
+    pub fn get_length(&self) -> ScalarQuantity {
+        return self.length;
+    }
+}
+
+/// Support radius of the spline kernel in units of the equivalent Plummer softening length.
+const SPLINE_SUPPORT: f64 = 2.8;
+
+impl SofteningKernel {
+    /// Returns `g(r)` such that acceleration caused by the mass `m` at the distance vector `d` is `G * m * g(r) * d`.
+    /// For the Newtonian interaction `g(r) = 1 / r^3`.
+    ///
+    /// `r2`: squared distance, `eps`: softening length in the same units
+    pub(crate) fn force_factor(&self, r2: f64, eps: f64) -> f64 {
+        match self {
+            Self::None => 1. / (r2 * r2.sqrt()),
+            Self::Plummer => {
+                let s2 = r2 + eps * eps;
+
+                1. / (s2 * s2.sqrt())
+            }
+            Self::Spline => {
+                let r = r2.sqrt();
+                let h = SPLINE_SUPPORT * eps;
+
+                if r >= h {
+                    return 1. / (r2 * r);
+                }
+
+                let u = r / h;
+                let h3 = h * h * h;
+
+                if u < 0.5 {
+                    (10.666666666667 + u * u * (32. * u - 38.4)) / h3
+                } else {
+                    (21.333333333333 - 48. * u + 38.4 * u * u
+                        - 10.666666666667 * u * u * u
+                        - 0.066666666667 / (u * u * u))
+                        / h3
+                }
+            }
+        }
+    }
+
+    /// Returns `g'(r) / r`, where `g(r)` is `force_factor`, so that the time derivative of the acceleration
+    /// caused by the mass `m` at the distance vector `d` moving with relative velocity `v`
+    /// is `G * m * (g(r) * v + g'(r) / r * (d, v) * d)`. For the Newtonian interaction it is `-3 / r^5`.
+    ///
+    /// `r2`: squared distance, `eps`: softening length in the same units
+    pub(crate) fn jerk_factor(&self, r2: f64, eps: f64) -> f64 {
+        match self {
+            Self::None => -3. / (r2 * r2 * r2.sqrt()),
+            Self::Plummer => {
+                let s2 = r2 + eps * eps;
+
+                -3. / (s2 * s2 * s2.sqrt())
+            }
+            Self::Spline => {
+                let r = r2.sqrt();
+                let h = SPLINE_SUPPORT * eps;
+
+                if r >= h {
+                    return -3. / (r2 * r2 * r);
+                }
+
+                let u = r / h;
+                let h5 = h * h * h * h * h;
+
+                if u < 0.5 {
+                    (96. * u - 76.8) / h5
+                } else {
+                    (-48. / u + 76.8 - 32. * u + 0.2 / (u * u * u * u * u)) / h5
+                }
+            }
+        }
+    }
+
+    /// Returns `p(r)` such that potential caused by the mass `m` at the distance `r` is `-G * m * p(r)`.
+    /// For the Newtonian interaction `p(r) = 1 / r`.
+    ///
+    /// `r2`: squared distance, `eps`: softening length in the same units
+    pub(crate) fn potential_factor(&self, r2: f64, eps: f64) -> f64 {
+        match self {
+            Self::None => 1. / r2.sqrt(),
+            Self::Plummer => 1. / (r2 + eps * eps).sqrt(),
+            Self::Spline => {
+                let r = r2.sqrt();
+                let h = SPLINE_SUPPORT * eps;
+
+                if r >= h {
+                    return 1. / r;
+                }
+
+                let u = r / h;
+
+                if u < 0.5 {
+                    -(-2.8 + u * u * (5.333333333333 + u * u * (6.4 * u - 9.6))) / h
+                } else {
+                    -(-3.2
+                        + 0.066666666667 / u
+                        + u * u * (10.666666666667 + u * (-16. + u * (9.6 - 2.133333333333 * u))))
+                        / h
+                }
+            }
+        }
+    }
+}
@@ -41,6 +41,15 @@ impl Vector3 {
         return self.x * v.x + self.y * v.y + self.z * v.z;
     }
 
+    /// Cross product of two vectors.
+    pub fn cross(&self, v: &Vector3) -> Vector3 {
+        return Vector3 {
+            x: self.y * v.z - self.z * v.y,
+            y: self.z * v.x - self.x * v.z,
+            z: self.x * v.y - self.y * v.x,
+        };
+    }
+
     /// Magnitude of the vector.
     pub fn mag(&self) -> f64 {
         return self.dot(self).sqrt();
@@ -0,0 +1,68 @@
+use xbody_model::generators::{plummer::Plummer, Generator};
+use xbody_model::integrators::barnes_hut::BarnesHut;
+use xbody_model::integrators::simple_nbody::SimpleNBody;
+use xbody_model::particles::ParticleSet;
+use xbody_model::quantity::{Units, VectorQuantity};
+
+fn plummer_sphere(n: usize) -> ParticleSet {
+    let plummer = Plummer::new(1. * Units::pc, n, 1e+4 * Units::MSun).unwrap();
+
+    plummer.generate_with_seed(1).unwrap()
+}
+
+/// Root mean square of relative differences between accelerations.
+fn relative_error(actual: &[VectorQuantity], expected: &[VectorQuantity]) -> f64 {
+    let mut sum = 0.;
+
+    for i in 0..actual.len() {
+        let diff = (actual[i] - expected[i]).mag() / expected[i].mag();
+        sum += diff.value_in_q(1. * Units::m / Units::m).powi(2);
+    }
+
+    (sum / actual.len() as f64).sqrt()
+}
+
+#[test]
+fn barnes_hut_negative_opening_angle() {
+    let ps = plummer_sphere(10);
+    let mut integrator = BarnesHut::new(&ps).unwrap();
+
+    assert!(integrator.set_opening_angle(-0.5).is_err());
+}
+
+#[test]
+fn barnes_hut_timestep_must_be_positive() {
+    let ps = plummer_sphere(10);
+    let mut integrator = BarnesHut::new(&ps).unwrap();
+
+    assert!(integrator.set_timestep(0. * Units::s).is_err());
+    assert!(integrator.set_timestep(-1. * Units::yr).is_err());
+    assert!(integrator.set_timestep(f64::NAN * Units::s).is_err());
+    assert!(integrator.set_timestep(1. * Units::yr).is_ok());
+}
+
+#[test]
+fn barnes_hut_zero_opening_angle_is_exact() {
+    let ps = plummer_sphere(100);
+    let mut integrator = BarnesHut::new(&ps).unwrap();
+    integrator.set_opening_angle(0.).unwrap();
+
+    let actual = integrator.get_accelerations();
+    let expected = SimpleNBody::new(&ps).unwrap().get_accelerations();
+
+    assert!(relative_error(&actual, &expected) < 1e-10);
+}
+
+#[test]
+fn barnes_hut_matches_direct_summation() {
+    let ps = plummer_sphere(300);
+    let expected = SimpleNBody::new(&ps).unwrap().get_accelerations();
+    let mut integrator = BarnesHut::new(&ps).unwrap();
+
+    let monopole = relative_error(&integrator.get_accelerations(), &expected);
+    integrator.set_quadrupole(true);
+    let quadrupole = relative_error(&integrator.get_accelerations(), &expected);
+
+    assert!(monopole < 0.02);
+    assert!(quadrupole < monopole);
+}
@@ -0,0 +1,113 @@
+use xbody_model::generators::binary::{Binary, HierarchicalTriple};
+use xbody_model::generators::{plummer::Plummer, Generator};
+use xbody_model::kepler::OrbitalElements;
+use xbody_model::particles::{Particle, ParticleSet};
+use xbody_model::quantity::Units;
+use xbody_model::vector::Vector3;
+use xbody_model::Error;
+
+fn assert_same_angle(actual: f64, expected: f64) {
+    let difference = (actual - expected).rem_euclid(2. * std::f64::consts::PI);
+
+    assert!(difference.min(2. * std::f64::consts::PI - difference) < 1e-12);
+}
+
+fn assert_same_orbit(actual: OrbitalElements, expected: OrbitalElements) {
+    let a = actual.get_semi_major_axis().value_si();
+    let b = expected.get_semi_major_axis().value_si();
+
+    assert!(((a - b) / b).abs() < 1e-12);
+    assert!((actual.get_eccentricity() - expected.get_eccentricity()).abs() < 1e-12);
+    assert!((actual.get_inclination() - expected.get_inclination()).abs() < 1e-12);
+    assert_same_angle(actual.get_longitude_of_ascending_node(), expected.get_longitude_of_ascending_node());
+    assert_same_angle(actual.get_argument_of_pericenter(), expected.get_argument_of_pericenter());
+    assert_same_angle(actual.get_mean_anomaly(), expected.get_mean_anomaly());
+}
+
+fn assert_at_rest(ps: &ParticleSet) -> Result<(), Error> {
+    let (r, v) = ps.get_center_of_mass()?;
+    let size = ps.particles.iter().map(|p| p.get_position().mag().value_in(Units::AU)).fold(0., f64::max);
+    let speed = ps.particles.iter().map(|p| p.get_velocity().mag().value_in(Units::kms)).fold(0., f64::max);
+
+    assert!(r.mag().value_in(Units::AU) < 1e-12 * size);
+    assert!(v.mag().value_in(Units::kms) < 1e-12 * speed);
+
+    Ok(())
+}
+
+#[test]
+fn binary_generate() -> Result<(), Error> {
+    let orbit = OrbitalElements::new(2. * Units::AU, 0.4, 0.3, 1., 2., 3.)?;
+    let ps = Binary::new(1. * Units::MSun, 0.5 * Units::MSun, orbit)?.generate()?;
+
+    assert_eq!(ps.particles.len(), 2);
+    assert_at_rest(&ps)?;
+    assert_same_orbit(OrbitalElements::from_particles(&ps.particles[0], &ps.particles[1])?, orbit);
+
+    Ok(())
+}
+
+#[test]
+fn binary_hierarchical_triple() -> Result<(), Error> {
+    let inner_orbit = OrbitalElements::new(1. * Units::AU, 0.2, 0., 0., 0., 1.)?;
+    let outer_orbit = OrbitalElements::new(20. * Units::AU, 0.3, 0.5, 1., 2., 3.)?;
+    let inner = Binary::new(1. * Units::MSun, 0.5 * Units::MSun, inner_orbit)?;
+    let ps = HierarchicalTriple::new(inner, 0.8 * Units::MSun, outer_orbit)?.generate()?;
+
+    assert_eq!(ps.particles.len(), 3);
+    assert_at_rest(&ps)?;
+    assert_same_orbit(OrbitalElements::from_particles(&ps.particles[0], &ps.particles[1])?, inner_orbit);
+
+    let (p1, p2) = (&ps.particles[0], &ps.particles[1]);
+    let mass = p1.get_mass() + p2.get_mass();
+    let inner_center = Particle::new(
+        (p1.get_position() * p1.get_mass() + p2.get_position() * p2.get_mass()) / mass,
+        (p1.get_velocity() * p1.get_mass() + p2.get_velocity() * p2.get_mass()) / mass,
+        mass,
+    )?;
+
+    assert_same_orbit(OrbitalElements::from_particles(&inner_center, &ps.particles[2])?, outer_orbit);
+
+    Ok(())
+}
+
+#[test]
+fn binary_is_composable() -> Result<(), Error> {
+    let orbit = OrbitalElements::new(100. * Units::AU, 0., 0., 0., 0., 0.)?;
+    let mut binary = Binary::new(10. * Units::MSun, 10. * Units::MSun, orbit)?.generate()?;
+    let offset = Vector3::new(0.1, 0., 0.) * Units::pc;
+    binary.shift(offset, Vector3::new(0., 5., 0.) * Units::kms)?;
+
+    let (r, _) = binary.get_center_of_mass()?;
+    assert!((r - offset).mag().value_in(Units::AU) < 1e-9);
+
+    let mut ps = Plummer::new(0.01 * Units::pc, 100, 100. * Units::MSun)?.generate()?;
+    ps.add_particles(binary);
+    ps.move_to_center_of_mass()?;
+
+    assert_eq!(ps.particles.len(), 102);
+    assert_at_rest(&ps)?;
+
+    Ok(())
+}
+
+#[test]
+fn binary_invalid_parameters() -> Result<(), Error> {
+    let inner_orbit = OrbitalElements::new(1. * Units::AU, 0.5, 0., 0., 0., 0.)?;
+    let outer_orbit = OrbitalElements::new(3. * Units::AU, 0.5, 0., 0., 0., 0.)?;
+    let flyby = OrbitalElements::new(-1. * Units::AU, 2., 0., 0., 0., 0.)?;
+
+    assert!(Binary::new(1. * Units::AU, 1. * Units::MSun, inner_orbit).is_err());
+    assert!(Binary::new(0. * Units::MSun, 1. * Units::MSun, inner_orbit).is_err());
+    assert!(Binary::new(1. * Units::MSun, -1. * Units::MSun, inner_orbit).is_err());
+    assert!(Binary::new(1. * Units::MSun, 0. * Units::MSun, flyby).is_ok());
+
+    // pericenter of the outer orbit is 1.5 AU, apocenter of the inner one is 1.5 AU
+    let inner = Binary::new(1. * Units::MSun, 1. * Units::MSun, inner_orbit)?;
+    assert!(HierarchicalTriple::new(inner, 1. * Units::MSun, outer_orbit).is_err());
+
+    let inner = Binary::new(1. * Units::MSun, 1. * Units::MSun, flyby)?;
+    assert!(HierarchicalTriple::new(inner, 1. * Units::MSun, outer_orbit).is_err());
+
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use xbody_model::integrators::block_timestep::BlockTimestep;
+use xbody_model::integrators::scheme::Scheme;
+use xbody_model::integrators::simple_nbody::SimpleNBody;
+use xbody_model::integrators::timestep::{AdaptiveTimestep, TimestepCriterion};
+use xbody_model::integrators::Integrator;
+use xbody_model::particles::{Particle, ParticleSet};
+use xbody_model::quantity::Units;
+use xbody_model::vector::Vector3;
+
+/// Sun with planets of small mass on circular orbits of 0.1 AU and 10 AU.
+fn hierarchical_system() -> ParticleSet {
+    let mut ps = ParticleSet::new().unwrap();
+    ps.add_particle(
+        Particle::new(
+            Vector3::null_vector() * Units::AU,
+            Vector3::null_vector() * Units::kms,
+            1. * Units::MSun,
+        )
+        .unwrap(),
+    );
+
+    for r in [0.1, 10.].iter() {
+        let v = (Units::G * Units::MSun.convert() / (*r * Units::AU)).pow(0.5);
+
+        ps.add_particle(
+            Particle::new(
+                Vector3::new(*r, 0., 0.) * Units::AU,
+                Vector3::new(0., 1., 0.) * v,
+                1e-8 * Units::MSun,
+            )
+            .unwrap(),
+        );
+    }
+
+    ps
+}
+
+fn aarseth(eta: f64) -> AdaptiveTimestep {
+    AdaptiveTimestep::new(TimestepCriterion::Aarseth, eta, 1e-8 * Units::yr, 1. * Units::yr).unwrap()
+}
+
+#[test]
+fn block_timestep_levels() {
+    let ps = hierarchical_system();
+    let mut integrator = BlockTimestep::new(&ps, aarseth(0.01)).unwrap();
+    integrator.evolve(&(0.1 * Units::yr)).unwrap();
+
+    let statistics = integrator.get_level_statistics();
+    let occupied: Vec<usize> = (0..statistics.len()).filter(|k| statistics[*k] > 0).collect();
+
+    // the Sun and the inner planet share the level, the outer planet has much larger timestep
+    assert_eq!(statistics.iter().sum::<usize>(), 3);
+    assert_eq!(occupied.len(), 2);
+    assert_eq!(statistics[occupied[1]], 2);
+    assert!(occupied[1] - occupied[0] >= 5);
+}
+
+#[test]
+fn block_timestep_matches_fixed_timestep() {
+    let ps = hierarchical_system();
+    let mut block = BlockTimestep::new(&ps, aarseth(0.002)).unwrap();
+    let mut fixed = SimpleNBody::with_scheme(&ps, Scheme::LeapfrogKDK).unwrap();
+    fixed.set_timestep(1e-5 * Units::yr).unwrap();
+
+    block.evolve(&(0.3 * Units::yr)).unwrap();
+    fixed.evolve(&(0.3 * Units::yr)).unwrap();
+
+    let block = block.get_state().unwrap();
+    let fixed = fixed.get_state().unwrap();
+
+    for i in 0..3 {
+        let d = block.particles[i].get_position() - fixed.particles[i].get_position();
+
+        assert_eq!(block.particles[i].get_id(), fixed.particles[i].get_id());
+        assert!(d.mag().value_in(Units::AU) < 1e-5);
+    }
+}
+
+#[test]
+fn block_timestep_conserves_energy() {
+    let ps = hierarchical_system();
+    let initial = ps.get_kinetic_energy() + ps.get_potential_energy();
+    let mut integrator = BlockTimestep::new(&ps, aarseth(0.01)).unwrap();
+
+    for i in 1..=10 {
+        integrator.evolve(&(i as f64 * Units::month)).unwrap();
+    }
+
+    let state = integrator.get_state().unwrap();
+    let last = state.get_kinetic_energy() + state.get_potential_energy();
+    assert!(((last - initial) / initial).value_in_q(Units::J.convert() / Units::J).abs() < 1e-8);
+}
+
+#[test]
+fn block_timestep_lands_on_end_time() {
+    let mut ps = ParticleSet::new().unwrap();
+    ps.add_particle(
+        Particle::new(
+            Vector3::null_vector() * Units::m,
+            Vector3::new(1., 0., 0.) * Units::ms,
+            1. * Units::kg,
+        )
+        .unwrap(),
+    );
+    let timestep =
+        AdaptiveTimestep::new(TimestepCriterion::FreeFall, 0.1, 0.1 * Units::s, 1. * Units::s).unwrap();
+    let mut integrator = BlockTimestep::new(&ps, timestep).unwrap();
+    integrator.evolve(&(10.5 * Units::s)).unwrap();
+
+    let x = integrator.get_state().unwrap().particles[0].get_position().value_in(Units::m).x;
+
+    assert!((x - 10.5).abs() < 1e-12);
+    assert!(integrator.evolve(&(1. * Units::s)).is_err());
+}
+
+#[test]
+fn block_timestep_isolated_particle_stays_on_top_level() {
+    let mut ps = ParticleSet::new().unwrap();
+    ps.add_particle(
+        Particle::new(
+            Vector3::null_vector() * Units::AU,
+            Vector3::new(1., 0., 0.) * Units::kms,
+            1. * Units::MSun,
+        )
+        .unwrap(),
+    );
+    let mut integrator = BlockTimestep::new(&ps, aarseth(0.01)).unwrap();
+    integrator.evolve(&(1. * Units::yr)).unwrap();
+
+    assert_eq!(integrator.get_level_statistics(), vec![1]);
+}
+
+#[test]
+fn block_timestep_rejects_too_many_levels() {
+    let ps = hierarchical_system();
+    let too_wide =
+        AdaptiveTimestep::new(TimestepCriterion::Aarseth, 0.01, 1e-20 * Units::yr, 1. * Units::yr).unwrap();
+
+    assert!(BlockTimestep::new(&ps, too_wide).is_err());
+
+    let mut integrator = BlockTimestep::new(&ps, aarseth(0.01)).unwrap();
+
+    assert!(integrator.set_timestep(too_wide).is_err());
+    assert!(integrator.set_timestep(aarseth(0.02)).is_ok());
+    assert_eq!(integrator.get_timestep().get_eta(), 0.02);
+}
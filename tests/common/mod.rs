@@ -0,0 +1,15 @@
+/// Asserts that the fraction of `radii` inside `r` is within five binomial standard deviations of `expected`.
+pub fn assert_cumulative_fraction(radii: &[f64], r: f64, expected: f64) {
+    let n = radii.len() as f64;
+    let actual = radii.iter().filter(|radius| **radius < r).count() as f64 / n;
+    let sigma = (expected * (1. - expected) / n).sqrt();
+
+    assert!(
+        (actual - expected).abs() < 5. * sigma,
+        "fraction inside {} is {}, expected {} ± {}",
+        r,
+        actual,
+        expected,
+        5. * sigma
+    );
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use xbody_model::error::Error;
+use xbody_model::generators::{dehnen::Dehnen, Generator};
+use xbody_model::particles::ParticleSet;
+use xbody_model::quantity::Units;
+
+fn radii(ps: &ParticleSet) -> Vec<f64> {
+    ps.particles.iter().map(|p| p.get_position().mag().value_in(Units::pc)).collect()
+}
+
+/// Isotropic velocity dispersion of Hernquist model (Hernquist, 1990, eq. 10) in the units of G M / a.
+fn hernquist_dispersion(r: f64) -> f64 {
+    (12. * r * (r + 1.).powi(3) * ((r + 1.) / r).ln() - r / (r + 1.) * (25. + 52. * r + 42. * r.powi(2) + 12. * r.powi(3)))
+        / 12.
+}
+
+#[test]
+fn dehnen_cumulative_mass_profile() -> Result<(), Error> {
+    let n = 5000;
+
+    for gamma in [0., 1., 2., 2.5] {
+        let radii = radii(&Dehnen::new(gamma, 1. * Units::pc, n, 1e+4 * Units::MSun)?.generate_with_seed(1)?);
+
+        for r in [0.1_f64, 0.5, 1., 3., 10.] {
+            let expected = (r / (r + 1.)).powf(3. - gamma);
+            common::assert_cumulative_fraction(&radii, r, expected);
+        }
+    }
+
+    let mut dehnen = Dehnen::hernquist(1. * Units::pc, n, 1e+4 * Units::MSun)?;
+    dehnen.set_truncation_radius(3. * Units::pc)?;
+
+    assert!(radii(&dehnen.generate_with_seed(2)?).iter().all(|r| *r <= 3.));
+
+    Ok(())
+}
+
+#[test]
+fn dehnen_hernquist_velocity_dispersion() -> Result<(), Error> {
+    let ps = Dehnen::hernquist(1. * Units::pc, 20000, 1e+4 * Units::MSun)?.generate_with_seed(3)?;
+    let unit = (Units::G * 1e+4 * Units::MSun / (1. * Units::pc)).value_in_q(Units::kms.pow(2.));
+    let mut ratio = 0.;
+    let mut count = 0;
+
+    for p in ps.particles.iter() {
+        let r = p.get_position().mag().value_in(Units::pc);
+        let v = p.get_velocity().mag().value_in(Units::kms);
+
+        // every particle is bound
+        assert!(v.powi(2) < 2. * unit / (1. + r));
+
+        if (0.3..5.).contains(&r) {
+            ratio += v.powi(2) / (3. * unit * hernquist_dispersion(r));
+            count += 1;
+        }
+    }
+
+    assert!((ratio / count as f64 - 1.).abs() < 0.03);
+
+    Ok(())
+}
+
+#[test]
+fn dehnen_truncated_equilibrium() -> Result<(), Error> {
+    for truncation in [1_f64, 3., 10.] {
+        let mut dehnen = Dehnen::hernquist(1. * Units::pc, 5000, 1e+4 * Units::MSun)?;
+        dehnen.set_truncation_radius(truncation * Units::pc)?;
+        let ps = dehnen.generate_with_seed(5)?;
+
+        // potential of the truncated spheroid: Hernquist one of mass `M` inside `r_t` matched to `-G m / r` outside it
+        let mass = 1e+4 * ((truncation + 1.) / truncation).powi(2);
+        let depth = |r: f64| -> f64 { mass / (1. + r) - mass / (1. + truncation) + 1e+4 / truncation };
+        let unit = (Units::G * Units::MSun.convert() / (1. * Units::pc)).value_in_q(Units::kms.pow(2.));
+        for p in ps.particles.iter() {
+            let r = p.get_position().mag().value_in(Units::pc);
+            let v = p.get_velocity().mag().value_in(Units::kms);
+
+            assert!(v.powi(2) < 2. * depth(r) * unit);
+        }
+
+        // pressure at the truncation radius of the untruncated spheroid, `4 π r_t^3 p(r_t) = 2 x^2 / (1 + x)^3 σ^2 G M^2 / a`;
+        // the cap of speeds lowers it noticeably only for the truncation at a few scale radii
+        let surface = 2. * truncation.powi(2) / (1. + truncation).powi(3) * hernquist_dispersion(truncation)
+            * Units::G
+            * (mass * Units::MSun).pow(2.)
+            / (1. * Units::pc);
+        let ratio = (2. * ps.get_kinetic_energy() - surface).value_in_q(-1. * ps.get_potential_energy());
+
+        if truncation >= 3. {
+            assert!((ratio - 1.).abs() < 0.1);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn dehnen_virial_equilibrium() -> Result<(), Error> {
+    for gamma in [0., 1.] {
+        let ps = Dehnen::new(gamma, 1. * Units::pc, 2000, 1e+4 * Units::MSun)?.generate_with_seed(4)?;
+        let ratio = (2. * ps.get_kinetic_energy()).value_in_q(-1. * ps.get_potential_energy());
+
+        assert!((ratio - 1.).abs() < 0.1);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn dehnen_invalid_parameters() -> Result<(), Error> {
+    assert!(Dehnen::new(-0.5, 1. * Units::pc, 10, 1. * Units::MSun).is_err());
+    assert!(Dehnen::new(3., 1. * Units::pc, 10, 1. * Units::MSun).is_err());
+    assert!(Dehnen::new(f64::NAN, 1. * Units::pc, 10, 1. * Units::MSun).is_err());
+    assert!(Dehnen::new(1., 0. * Units::pc, 10, 1. * Units::MSun).is_err());
+    assert!(Dehnen::new(1., 1. * Units::pc, 0, 1. * Units::MSun).is_err());
+    assert!(Dehnen::new(1., 1. * Units::pc, 10, 0. * Units::MSun).is_err());
+
+    let mut dehnen = Dehnen::hernquist(1. * Units::pc, 10, 1. * Units::MSun)?;
+
+    assert!(dehnen.set_truncation_radius(0. * Units::pc).is_err());
+    assert_eq!(dehnen.get_gamma(), 1.);
+
+    Ok(())
+}
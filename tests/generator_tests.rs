@@ -0,0 +1,41 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use xbody_model::error::Error;
+use xbody_model::generators::{
+    dehnen::Dehnen, king::King, nfw::NFW, plummer::Plummer, spherical::Spherical, Generator,
+};
+use xbody_model::quantity::Units;
+
+/// All random generators with small number of particles.
+fn random_generators() -> Result<Vec<Box<dyn Generator>>, Error> {
+    let plummer = |x: f64| -> f64 { (1. + x.powi(2)).powf(-2.5) };
+    let mut spherical = Spherical::new(plummer, 1. * Units::pc, 100, 1e+3 * Units::MSun)?;
+    spherical.set_anisotropy_radius(2. * Units::pc)?;
+
+    Ok(vec![
+        Box::new(Plummer::new(1. * Units::pc, 100, 1e+3 * Units::MSun)?),
+        Box::new(King::with_core_radius(5., 100, 1e+3 * Units::MSun, 1. * Units::pc)?),
+        Box::new(Dehnen::jaffe(1. * Units::pc, 100, 1e+3 * Units::MSun)?),
+        Box::new(NFW::new(1e+12 * Units::MSun, 10., 100)?),
+        Box::new(spherical),
+    ])
+}
+
+#[test]
+fn generators_seeded_generation_is_reproducible() -> Result<(), Error> {
+    for generator in random_generators()? {
+        let first = generator.generate_with_seed(42)?;
+        let second = generator.generate_with_seed(42)?;
+        let from_rng = generator.generate_with_rng(&mut ChaCha20Rng::seed_from_u64(42))?;
+        let other = generator.generate_with_seed(43)?;
+
+        for i in 0..first.particles.len() {
+            assert!(first.particles[i] == second.particles[i]);
+            assert!(first.particles[i] == from_rng.particles[i]);
+            assert_eq!(first.particles[i].get_id(), second.particles[i].get_id());
+        }
+        assert!((0..other.particles.len()).all(|i| first.particles[i] != other.particles[i]));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use xbody_model::integrators::hermite::Hermite;
+use xbody_model::integrators::scheme::Scheme;
+use xbody_model::integrators::simple_nbody::SimpleNBody;
+use xbody_model::integrators::timestep::{AdaptiveTimestep, TimestepCriterion};
+use xbody_model::integrators::Integrator;
+use xbody_model::particles::{Particle, ParticleSet};
+use xbody_model::quantity::{ScalarQuantity, Units};
+use xbody_model::softening::{Softening, SofteningKernel};
+use xbody_model::vector::Vector3;
+
+/// Sun and a planet of small mass at aphelion of the orbit with semi-major axis of 1 AU and eccentricity `e`.
+fn eccentric_orbit(e: f64) -> ParticleSet {
+    let mut ps = ParticleSet::new().unwrap();
+    let v = (Units::G * Units::MSun.convert() / Units::AU.convert() * (1. - e) / (1. + e)).pow(0.5);
+
+    ps.add_particle(
+        Particle::new(
+            Vector3::null_vector() * Units::AU,
+            Vector3::null_vector() * Units::kms,
+            1. * Units::MSun,
+        )
+        .unwrap(),
+    );
+    ps.add_particle(
+        Particle::new(
+            Vector3::new(1. + e, 0., 0.) * Units::AU,
+            Vector3::new(0., 1., 0.) * v,
+            1e-6 * Units::MSun,
+        )
+        .unwrap(),
+    );
+
+    ps
+}
+
+fn energy(ps: &ParticleSet) -> ScalarQuantity {
+    ps.get_kinetic_energy() + ps.get_potential_energy()
+}
+
+fn relative_energy_error(initial: &ParticleSet, integrator: &impl Integrator) -> f64 {
+    let error = (energy(&integrator.get_state().unwrap()) - energy(initial)) / energy(initial);
+
+    error.value_in_q(Units::J.convert() / Units::J).abs()
+}
+
+fn hermite_energy_error(dt: f64, softening: Softening) -> f64 {
+    let ps = eccentric_orbit(0.5);
+    let mut integrator = Hermite::new(&ps).unwrap();
+    integrator.set_timestep(dt * Units::yr).unwrap();
+    integrator.set_softening(softening);
+    integrator.evolve(&(0.5 * Units::yr)).unwrap();
+
+    let state = integrator.get_state().unwrap();
+    let error = (energy_with(&state, &softening) - energy_with(&ps, &softening)) / energy_with(&ps, &softening);
+
+    error.value_in_q(Units::J.convert() / Units::J).abs()
+}
+
+fn energy_with(ps: &ParticleSet, softening: &Softening) -> ScalarQuantity {
+    ps.get_kinetic_energy() + ps.get_softened_potential_energy(softening)
+}
+
+#[test]
+fn hermite_fourth_order() {
+    // orbit goes from 0.5 AU to 1.5 AU, so with softening it passes through all parts of the kernels
+    let softenings = [
+        Softening::none(),
+        Softening::new(SofteningKernel::Plummer, 0.3 * Units::AU).unwrap(),
+        Softening::new(SofteningKernel::Spline, 0.4 * Units::AU).unwrap(),
+    ];
+
+    for softening in softenings.iter() {
+        let coarse = hermite_energy_error(1. / 200., *softening);
+        let fine = hermite_energy_error(1. / 400., *softening);
+
+        // the error of fourth-order method decreases 16 times when timestep is halved
+        assert!(coarse / fine > 10.);
+        assert!(coarse / fine < 25.);
+    }
+}
+
+/// Position of the planet relative to the Sun after 10 years.
+fn planet_position(integrator: &mut impl Integrator) -> Vector3 {
+    for i in 1..=10 {
+        integrator.evolve(&(i as f64 * Units::yr)).unwrap();
+    }
+
+    let state = integrator.get_state().unwrap();
+
+    (state.particles[1].get_position() - state.particles[0].get_position()).value_in(Units::AU)
+}
+
+#[test]
+fn hermite_more_accurate_than_leapfrog() {
+    let ps = eccentric_orbit(0.5);
+    let mut reference = Hermite::new(&ps).unwrap();
+    let mut hermite = Hermite::new(&ps).unwrap();
+    let mut leapfrog = SimpleNBody::with_scheme(&ps, Scheme::LeapfrogKDK).unwrap();
+    reference.set_timestep(0.1 * Units::day).unwrap();
+    hermite.set_timestep(1. * Units::day).unwrap();
+    leapfrog.set_timestep(1. * Units::day).unwrap();
+
+    let expected = planet_position(&mut reference);
+    let hermite_error = (planet_position(&mut hermite) - expected).mag();
+    let leapfrog_error = (planet_position(&mut leapfrog) - expected).mag();
+
+    assert!(hermite_error < leapfrog_error / 50.);
+}
+
+#[test]
+fn hermite_aarseth_timestep() {
+    let ps = eccentric_orbit(0.9);
+    let adaptive =
+        AdaptiveTimestep::new(TimestepCriterion::Aarseth, 0.05, 1e-8 * Units::yr, 0.01 * Units::yr).unwrap();
+    let mut integrator = Hermite::new(&ps).unwrap();
+    integrator.set_adaptive_timestep(adaptive);
+
+    for i in 1..=10 {
+        integrator.evolve(&(i as f64 * Units::yr)).unwrap();
+    }
+
+    let state = integrator.get_state().unwrap();
+    let d = state.particles[1].get_position() - state.particles[0].get_position();
+    assert!(relative_energy_error(&ps, &integrator) < 1e-5);
+    assert!(d.mag().value_in(Units::AU) > 1.8);
+    assert!(integrator.get_timestep().value_in(Units::yr) <= 0.01);
+}
+
+#[test]
+fn hermite_parameters() {
+    let ps = eccentric_orbit(0.5);
+    let mut integrator = Hermite::new(&ps).unwrap();
+
+    assert!(integrator.set_timestep(0. * Units::s).is_err());
+    assert!(integrator.set_timestep(-1. * Units::day).is_err());
+    assert!(integrator.set_timestep(f64::NAN * Units::s).is_err());
+    assert!(integrator.set_timestep(1. * Units::m).is_err());
+    assert!(integrator.set_timestep(1. * Units::day).is_ok());
+    assert!(integrator.get_adaptive_timestep().is_none());
+    assert!((integrator.get_timestep().value_in(Units::day) - 1.).abs() < 1e-12);
+}
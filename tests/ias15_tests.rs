@@ -0,0 +1,95 @@
+use xbody_model::integrators::ias15::IAS15;
+use xbody_model::integrators::Integrator;
+use xbody_model::particles::{Particle, ParticleSet};
+use xbody_model::quantity::{ScalarQuantity, Units};
+use xbody_model::vector::Vector3;
+
+/// Sun and Jupiter-like planet at aphelion of the orbit with semi-major axis of 1 AU and eccentricity `e`;
+/// centre of mass is at rest.
+fn kepler_orbit(e: f64) -> ParticleSet {
+    let mut ps = ParticleSet::new().unwrap();
+    let q = 1e-3;
+    let m = q * Units::MSun;
+    let v = (Units::G * (Units::MSun.convert() + m) / Units::AU.convert() * (1. - e) / (1. + e)).pow(0.5);
+
+    ps.add_particle(
+        Particle::new(
+            Vector3::null_vector() * Units::AU,
+            Vector3::new(0., -q / (1. + q), 0.) * v,
+            1. * Units::MSun,
+        )
+        .unwrap(),
+    );
+    ps.add_particle(
+        Particle::new(
+            Vector3::new(1. + e, 0., 0.) * Units::AU,
+            Vector3::new(0., 1. / (1. + q), 0.) * v,
+            m,
+        )
+        .unwrap(),
+    );
+
+    ps
+}
+
+fn period() -> ScalarQuantity {
+    let m = 1.001 * Units::MSun;
+
+    2. * std::f64::consts::PI * (Units::AU.convert().pow(3.) / (Units::G * m)).pow(0.5)
+}
+
+fn energy(ps: &ParticleSet) -> f64 {
+    (ps.get_kinetic_energy() + ps.get_potential_energy()).value_in(Units::J)
+}
+
+fn energy_error(orbits: f64) -> f64 {
+    let ps = kepler_orbit(0.5);
+    let mut integrator = IAS15::new(&ps).unwrap();
+    integrator.set_timestep(0.01 * period()).unwrap();
+    integrator.evolve(&(orbits * period())).unwrap();
+
+    let state = integrator.get_state().unwrap();
+
+    ((energy(&state) - energy(&ps)) / energy(&ps)).abs()
+}
+
+#[test]
+fn ias15_kepler_orbit() {
+    let ps = kepler_orbit(0.5);
+    let mut integrator = IAS15::new(&ps).unwrap();
+    integrator.set_timestep(0.01 * period()).unwrap();
+    integrator.evolve(&(10. * period())).unwrap();
+
+    let state = integrator.get_state().unwrap();
+    let initial = ps.particles[1].get_position() - ps.particles[0].get_position();
+    let last = state.particles[1].get_position() - state.particles[0].get_position();
+
+    // after whole number of periods the planet returns to aphelion
+    assert!((last - initial).mag().value_in(Units::AU) < 1e-11);
+}
+
+#[test]
+fn ias15_energy_error_1e3_orbits() {
+    assert!(energy_error(1e3) < 1e-13);
+}
+
+/// Takes about twenty minutes, so it is ignored by default; run it with
+/// `cargo test --release --test ias15_tests -- --ignored`.
+#[test]
+#[ignore]
+fn ias15_energy_error_1e6_orbits() {
+    assert!(energy_error(1e6) < 1e-12);
+}
+
+#[test]
+fn ias15_parameters() {
+    let ps = kepler_orbit(0.5);
+    let mut integrator = IAS15::new(&ps).unwrap();
+
+    assert_eq!(integrator.get_epsilon(), 1e-9);
+    assert!(integrator.set_epsilon(-1.).is_err());
+    assert!(integrator.set_timestep(0. * Units::s).is_err());
+    assert!(integrator.set_timestep(-1. * Units::s).is_err());
+    assert!(integrator.set_timestep(f64::INFINITY * Units::s).is_err());
+    assert!(integrator.set_timestep(1. * Units::m).is_err());
+}
@@ -0,0 +1,146 @@
+use std::f64::consts::PI;
+use xbody_model::error::Error;
+use xbody_model::integrators::ias15::IAS15;
+use xbody_model::integrators::Integrator;
+use xbody_model::kepler::{propagate, OrbitalElements};
+use xbody_model::particles::{Particle, ParticleSet};
+use xbody_model::quantity::{ScalarQuantity, Units, VectorQuantity};
+use xbody_model::vector::Vector3;
+
+fn relative_difference(actual: VectorQuantity, expected: VectorQuantity) -> f64 {
+    (actual - expected).mag().value_in_q(expected.mag())
+}
+
+fn check_round_trip(elements: OrbitalElements) -> Result<(), Error> {
+    let mass = 1. * Units::MSun;
+    let (r, v) = elements.to_state(mass)?;
+    let restored = OrbitalElements::from_state(r, v, mass)?;
+    let (r1, v1) = restored.to_state(mass)?;
+
+    assert!(relative_difference(r1, r) < 1e-12);
+    assert!(relative_difference(v1, v) < 1e-12);
+    assert!((restored.get_eccentricity() - elements.get_eccentricity()).abs() < 1e-12);
+    assert!((restored.get_inclination() - elements.get_inclination()).abs() < 1e-12);
+
+    Ok(())
+}
+
+#[test]
+fn kepler_elements_round_trip() -> Result<(), Error> {
+    check_round_trip(OrbitalElements::new(1. * Units::AU, 0.3, 0.5, 1., 2., 3.)?)?;
+    check_round_trip(OrbitalElements::new(2. * Units::AU, 0.99, 2.5, 4., 5., 0.1)?)?;
+    check_round_trip(OrbitalElements::new(-1. * Units::AU, 1.5, 1., 0.5, 1., -2.)?)?;
+    check_round_trip(OrbitalElements::with_pericenter(1. * Units::AU, 1., 0.2, 0.3, 0.4, 1.5)?)?;
+    check_round_trip(OrbitalElements::with_pericenter(1. * Units::AU, 1., 0.2, 0.3, 0.4, -1.5)?)?;
+
+    let elements = OrbitalElements::new(1. * Units::AU, 0.3, 0.5, 1., 2., 3.)?;
+    let (r, v) = elements.to_state(1. * Units::MSun)?;
+    let restored = OrbitalElements::from_state(r, v, 1. * Units::MSun)?;
+
+    assert!((restored.get_semi_major_axis().value_in(Units::AU)? - 1.).abs() < 1e-12);
+    assert!((restored.get_longitude_of_ascending_node() - 1.).abs() < 1e-12);
+    assert!((restored.get_argument_of_pericenter() - 2.).abs() < 1e-12);
+    assert!((restored.get_mean_anomaly() - 3.).abs() < 1e-12);
+
+    Ok(())
+}
+
+#[test]
+fn kepler_degenerate_orbits() -> Result<(), Error> {
+    // circular equatorial orbit: the whole phase goes to mean anomaly
+    let elements = OrbitalElements::new(1. * Units::AU, 0., 0., 0., 0., 1.)?;
+    let (r, _) = elements.to_state(1. * Units::MSun)?;
+    let expected = Vector3::new(1_f64.cos(), 1_f64.sin(), 0.) * Units::AU;
+    assert!(relative_difference(r, expected) < 1e-14);
+
+    check_round_trip(elements)?;
+    check_round_trip(OrbitalElements::new(1. * Units::AU, 0.5, PI, 0., 1., 2.)?)?;
+    check_round_trip(OrbitalElements::new(1. * Units::AU, 0., 1., 2., 0., 3.)?)?;
+
+    Ok(())
+}
+
+#[test]
+fn kepler_propagate() -> Result<(), Error> {
+    let mass = 1. * Units::MSun;
+
+    for elements in [
+        OrbitalElements::new(1. * Units::AU, 0.7, 0.5, 1., 2., 3.)?,
+        OrbitalElements::new(-1. * Units::AU, 3., 0.5, 1., 2., -1.)?,
+        OrbitalElements::with_pericenter(1. * Units::AU, 1., 0.5, 1., 2., -1.)?,
+    ] {
+        let q = elements.get_pericenter_distance().value_si();
+        let e = elements.get_eccentricity();
+        let mu = (Units::G * mass).value_in_q(Units::m.pow(3.) / Units::s.pow(2.));
+        let mean_motion = if e == 1. {
+            (mu / (2. * q.powi(3))).sqrt()
+        } else {
+            (mu / (q / (1. - e)).abs().powi(3)).sqrt()
+        };
+        let time = 0.5 / mean_motion;
+
+        let (r0, v0) = elements.to_state(mass)?;
+        let (r, v) = propagate(r0, v0, mass, time * Units::s)?;
+
+        let later = OrbitalElements::with_pericenter(
+            elements.get_pericenter_distance(),
+            e,
+            elements.get_inclination(),
+            elements.get_longitude_of_ascending_node(),
+            elements.get_argument_of_pericenter(),
+            elements.get_mean_anomaly() + 0.5,
+        )?;
+        let (r1, v1) = later.to_state(mass)?;
+
+        assert!(relative_difference(r, r1) < 1e-12);
+        assert!(relative_difference(v, v1) < 1e-12);
+
+        // and back
+        let (r2, v2) = propagate(r, v, mass, -time * Units::s)?;
+
+        assert!(relative_difference(r2, r0) < 1e-12);
+        assert!(relative_difference(v2, v0) < 1e-12);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn kepler_reference_for_integrator() -> Result<(), Error> {
+    let mass = 1. * Units::MSun;
+    let elements = OrbitalElements::new(1. * Units::AU, 0.6, 0.3, 0., 0., 0.)?;
+    let (r, v) = elements.to_state(mass)?;
+    let time: ScalarQuantity = 3.7 * Units::yr;
+
+    let mut ps = ParticleSet::new()?;
+    ps.add_particle(Particle::new(Vector3::null_vector() * Units::AU, Vector3::null_vector() * Units::kms, mass)?);
+    ps.add_particle(Particle::new(r, v, 0. * Units::kg)?);
+
+    let mut integrator = IAS15::new(&ps)?;
+    integrator.evolve(&time)?;
+
+    let state = integrator.get_state()?;
+    let (expected, _) = propagate(r, v, mass, time)?;
+
+    assert!(relative_difference(state.particles[1].get_position(), expected) < 1e-10);
+
+    Ok(())
+}
+
+#[test]
+fn kepler_invalid_parameters() {
+    assert!(OrbitalElements::new(1. * Units::AU, 1., 0., 0., 0., 0.).is_err());
+    assert!(OrbitalElements::new(-1. * Units::AU, 0.5, 0., 0., 0., 0.).is_err());
+    assert!(OrbitalElements::new(1. * Units::AU, 2., 0., 0., 0., 0.).is_err());
+    assert!(OrbitalElements::new(1. * Units::AU, -0.1, 0., 0., 0., 0.).is_err());
+    assert!(OrbitalElements::new(1. * Units::AU, 0.5, 4., 0., 0., 0.).is_err());
+    assert!(OrbitalElements::new(1. * Units::kg, 0.5, 0., 0., 0., 0.).is_err());
+    assert!(OrbitalElements::with_pericenter(0. * Units::AU, 1., 0., 0., 0., 0.).is_err());
+
+    let r = Vector3::new(1., 0., 0.) * Units::AU;
+    let v = Vector3::new(1., 0., 0.) * Units::kms;
+
+    assert!(OrbitalElements::from_state(r, v, 1. * Units::MSun).is_err());
+    assert!(OrbitalElements::from_state(r, v * 0., 0. * Units::MSun).is_err());
+    assert!(propagate(r * 0., v, 1. * Units::MSun, 1. * Units::yr).is_err());
+}
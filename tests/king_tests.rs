@@ -0,0 +1,70 @@
+mod common;
+
+use xbody_model::error::Error;
+use xbody_model::generators::{king::King, Generator};
+use xbody_model::quantity::Units;
+
+#[test]
+fn king_concentration() -> Result<(), Error> {
+    // King (1966), Binney & Tremaine (2008), fig. 4.9
+    for (w0, concentration) in [(3., 0.67), (6., 1.26), (7., 1.53), (9., 2.12)] {
+        let king = King::with_core_radius(w0, 10, 1e+4 * Units::MSun, 1. * Units::pc)?;
+
+        assert!((king.get_concentration() - concentration).abs() < 0.01);
+    }
+
+    let by_core = King::with_core_radius(6., 10, 1e+4 * Units::MSun, 1. * Units::pc)?;
+    let by_tidal = King::with_tidal_radius(6., 10, 1e+4 * Units::MSun, by_core.get_tidal_radius())?;
+
+    assert!((by_tidal.get_core_radius().value_in(Units::pc)? - 1.).abs() < 1e-12);
+
+    Ok(())
+}
+
+#[test]
+fn king_cumulative_mass_profile() -> Result<(), Error> {
+    let n = 5000;
+    let king = King::with_core_radius(6., n, 1e+4 * Units::MSun, 1. * Units::pc)?;
+    let ps = king.generate_with_seed(1)?;
+    let tidal_radius = king.get_tidal_radius().value_in(Units::pc)?;
+    let radii: Vec<f64> = ps.particles.iter().map(|p| p.get_position().mag().value_in(Units::pc)).collect();
+
+    assert_eq!(radii.len(), n);
+    assert!(radii.iter().all(|r| *r <= tidal_radius));
+
+    for r in [0.3, 0.7, 1., 2., 4., 8.] {
+        let expected = king.get_enclosed_mass(r * Units::pc)?.value_in(Units::MSun)? / 1e+4;
+        common::assert_cumulative_fraction(&radii, r, expected);
+    }
+
+    assert_eq!(king.get_enclosed_mass(tidal_radius * Units::pc)?.value_in(Units::MSun)?, 1e+4);
+
+    Ok(())
+}
+
+#[test]
+fn king_virial_equilibrium() -> Result<(), Error> {
+    for w0 in [3., 7.] {
+        let ps = King::with_core_radius(w0, 2000, 1e+4 * Units::MSun, 1. * Units::pc)?.generate_with_seed(2)?;
+        let ratio = (2. * ps.get_kinetic_energy()).value_in_q(-1. * ps.get_potential_energy());
+
+        assert!((ratio - 1.).abs() < 0.1);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn king_invalid_parameters() {
+    assert!(King::with_core_radius(0., 10, 1. * Units::MSun, 1. * Units::pc).is_err());
+    assert!(King::with_core_radius(f64::NAN, 10, 1. * Units::MSun, 1. * Units::pc).is_err());
+    assert!(King::with_core_radius(6., 0, 1. * Units::MSun, 1. * Units::pc).is_err());
+    assert!(King::with_core_radius(6., 10, 0. * Units::MSun, 1. * Units::pc).is_err());
+    assert!(King::with_core_radius(6., 10, 1. * Units::MSun, 0. * Units::pc).is_err());
+    assert!(King::with_tidal_radius(6., 10, 1. * Units::pc, 1. * Units::MSun).is_err());
+
+    let king = King::with_core_radius(6., 10, 1. * Units::MSun, 1. * Units::pc).unwrap();
+
+    assert!(king.get_enclosed_mass(f64::NAN * Units::pc).is_err());
+    assert!(king.get_enclosed_mass(f64::INFINITY * Units::pc).is_err());
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use xbody_model::error::Error;
+use xbody_model::generators::dehnen::Dehnen;
+use xbody_model::generators::{nfw::NFW, Generator};
+use xbody_model::quantity::Units;
+
+fn nfw_mass_fraction(x: f64) -> f64 {
+    (1. + x).ln() - x / (1. + x)
+}
+
+#[test]
+fn nfw_virial_radius() -> Result<(), Error> {
+    let mut nfw = NFW::new(1e+12 * Units::MSun, 10., 100)?;
+
+    assert!((nfw.get_virial_radius().value_in(Units::kpc)? - 206.2).abs() < 0.1);
+    assert!((nfw.get_scale_radius().value_in(Units::kpc)? - 20.62).abs() < 0.01);
+    assert!((nfw.get_decay_radius().value_in(Units::kpc)? - 20.62).abs() < 0.01);
+
+    nfw.set_virial_radius(100. * Units::kpc)?;
+
+    assert!((nfw.get_scale_radius().value_in(Units::kpc)? - 10.).abs() < 1e-12);
+    assert!((nfw.get_decay_radius().value_in(Units::kpc)? - 10.).abs() < 1e-12);
+
+    nfw.set_decay_radius(5. * Units::kpc)?;
+
+    assert!((nfw.get_decay_radius().value_in(Units::kpc)? - 5.).abs() < 1e-12);
+
+    Ok(())
+}
+
+#[test]
+fn nfw_cumulative_mass_profile() -> Result<(), Error> {
+    let n = 5000;
+    let c = 10.;
+    let nfw = NFW::new(1e+12 * Units::MSun, c, n)?;
+    let ps = nfw.generate_with_seed(1)?;
+    let scale_radius = nfw.get_scale_radius().value_in(Units::kpc)?;
+    let virial_fraction = 1e+12 / nfw.get_total_mass()?.value_in(Units::MSun)?;
+    let radii: Vec<f64> = ps.particles.iter().map(|p| p.get_position().mag().value_in(Units::kpc) / scale_radius).collect();
+
+    assert!(virial_fraction > 0.75 && virial_fraction < 0.85);
+    assert!((ps.get_total_mass().value_in(Units::MSun) * virial_fraction / 1e+12 - 1.).abs() < 1e-12);
+
+    for x in [0.1, 0.5, 1., 3., 10., 12.] {
+        if x <= c {
+            common::assert_cumulative_fraction(&radii, x, virial_fraction * nfw_mass_fraction(x) / nfw_mass_fraction(c));
+        } else {
+            // the cutoff only adds mass
+            assert!(radii.iter().filter(|r| **r < x).count() as f64 / n as f64 > virial_fraction);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn nfw_virial_equilibrium() -> Result<(), Error> {
+    for c in [5., 15.] {
+        let ps = NFW::new(1e+12 * Units::MSun, c, 2000)?.generate_with_seed(2)?;
+        let ratio = (2. * ps.get_kinetic_energy()).value_in_q(-1. * ps.get_potential_energy());
+
+        assert!((ratio - 1.).abs() < 0.1);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn nfw_with_bulge() -> Result<(), Error> {
+    let nfw = NFW::new(1e+12 * Units::MSun, 10., 1000)?;
+    let bulge = Dehnen::hernquist(1. * Units::kpc, 200, 1e+10 * Units::MSun)?;
+
+    let mut ps = nfw.generate_with_seed(1)?;
+    ps.add_particles(bulge.generate_with_seed(2)?);
+    ps.move_to_center_of_mass()?;
+
+    assert_eq!(ps.particles.len(), 1200);
+
+    Ok(())
+}
+
+#[test]
+fn nfw_invalid_parameters() -> Result<(), Error> {
+    assert!(NFW::new(0. * Units::MSun, 10., 10).is_err());
+    assert!(NFW::new(1. * Units::pc, 10., 10).is_err());
+    assert!(NFW::new(1e+12 * Units::MSun, 0., 10).is_err());
+    assert!(NFW::new(1e+12 * Units::MSun, f64::INFINITY, 10).is_err());
+    assert!(NFW::new(1e+12 * Units::MSun, 10., 0).is_err());
+
+    let mut nfw = NFW::new(1e+12 * Units::MSun, 10., 10)?;
+
+    assert!(nfw.set_virial_radius(0. * Units::kpc).is_err());
+    assert!(nfw.set_decay_radius(-1. * Units::kpc).is_err());
+    assert!(nfw.set_decay_radius(1. * Units::MSun).is_err());
+
+    Ok(())
+}
@@ -0,0 +1,34 @@
+#![cfg(feature = "parallel")]
+
+use rayon::ThreadPoolBuilder;
+use xbody_model::generators::{plummer::Plummer, Generator};
+use xbody_model::integrators::barnes_hut::BarnesHut;
+use xbody_model::integrators::simple_nbody::SimpleNBody;
+use xbody_model::quantity::Units;
+
+fn in_pool<T: Send, F: FnOnce() -> T + Send>(threads: usize, f: F) -> T {
+    let pool = ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+
+    pool.install(f)
+}
+
+#[test]
+fn parallel_results_do_not_depend_on_threads() {
+    let plummer = Plummer::new(1. * Units::pc, 200, 1e+4 * Units::MSun).unwrap();
+    let ps = plummer.generate_with_seed(1).unwrap();
+    let direct = SimpleNBody::new(&ps).unwrap();
+    let tree = BarnesHut::new(&ps).unwrap();
+
+    assert_eq!(
+        in_pool(1, || direct.get_accelerations()),
+        in_pool(8, || direct.get_accelerations())
+    );
+    assert_eq!(
+        in_pool(1, || tree.get_accelerations()),
+        in_pool(8, || tree.get_accelerations())
+    );
+    assert_eq!(
+        in_pool(1, || ps.get_potential_energy()),
+        in_pool(8, || ps.get_potential_energy())
+    );
+}
@@ -0,0 +1,153 @@
+use xbody_model::generators::{plummer::Plummer, Generator};
+use xbody_model::integrators::barnes_hut::BarnesHut;
+use xbody_model::integrators::{simple_nbody::SimpleNBody, Integrator};
+use xbody_model::particles::{Particle, ParticleSet};
+use xbody_model::quantity::Units;
+use xbody_model::vector::Vector3;
+use xbody_model::Error;
+
+fn particle_set() -> Result<ParticleSet, Error> {
+    let mut ps = ParticleSet::new()?;
+
+    ps.add_particle(Particle::new(
+        Vector3::new(1., 0., 0.) * Units::pc,
+        Vector3::new(0., 1., 0.) * Units::kms,
+        1. * Units::MSun,
+    )?);
+
+    Ok(ps)
+}
+
+#[test]
+fn particle_new() {
+    let ps = particle_set().unwrap();
+
+    assert_eq!(ps.particles[0].get_mass(), 1. * Units::MSun);
+}
+
+#[test]
+fn particle_incompatible_position() {
+    let actual = Particle::new(
+        Vector3::new(1., 0., 0.) * Units::s,
+        Vector3::null_vector() * Units::kms,
+        1. * Units::MSun,
+    );
+
+    match actual {
+        Err(Error::IncompatibleUnits { name, expected, .. }) => {
+            assert_eq!(name, "position");
+            assert_eq!(expected, "m^1 s^0 kg^0");
+        }
+        _ => panic!("expected incompatible units error"),
+    }
+}
+
+#[test]
+fn particle_negative_mass() {
+    let actual = Particle::new(
+        Vector3::null_vector() * Units::pc,
+        Vector3::null_vector() * Units::kms,
+        -1. * Units::MSun,
+    );
+
+    assert!(matches!(
+        actual,
+        Err(Error::InvalidParameter { name: "mass", .. })
+    ));
+}
+
+#[test]
+fn error_display() {
+    let actual = Error::EmptySet.to_string();
+
+    assert_eq!(actual, "set of particles is empty");
+}
+
+#[test]
+fn particle_ids_are_unique() {
+    let plummer = Plummer::new(1. * Units::pc, 50, 1e+3 * Units::MSun).unwrap();
+    let mut ps = plummer.generate().unwrap();
+
+    assert!((0..50).all(|i| ps.particles[i].get_id() == i as u64));
+
+    ps.add_particles(plummer.generate().unwrap());
+    let mut ids: Vec<u64> = ps.particles.iter().map(|p| p.get_id()).collect();
+
+    ids.sort_unstable();
+    ids.dedup();
+
+    assert_eq!(ids.len(), 100);
+}
+
+#[test]
+fn restored_particles_keep_ids() -> Result<(), Error> {
+    let mut ps = ParticleSet::new()?;
+    let mut p = Particle::new(Vector3::null_vector() * Units::m, Vector3::null_vector() * Units::ms, 1. * Units::kg)?;
+    p.set_id(7);
+    ps.restore_particle(p);
+    ps.add_particle(Particle::new(Vector3::null_vector() * Units::m, Vector3::null_vector() * Units::ms, 1. * Units::kg)?);
+
+    assert_eq!(ps.particles[0].get_id(), 7);
+    assert_eq!(ps.particles[1].get_id(), 8);
+
+    Ok(())
+}
+
+#[test]
+fn particle_set_get_by_id() {
+    let mut ps = particle_set().unwrap();
+    let id = ps.particles[0].get_id();
+
+    ps.get_by_id_mut(id)
+        .unwrap()
+        .set_mass(2. * Units::MSun)
+        .unwrap();
+
+    assert_eq!(ps.get_by_id(id).unwrap().get_mass(), 2. * Units::MSun);
+    assert!(ps.get_by_id(id + 1).is_none());
+}
+
+#[test]
+fn particle_ids_are_preserved_by_integrators() {
+    let plummer = Plummer::new(1. * Units::pc, 20, 1e+3 * Units::MSun).unwrap();
+    let ps = plummer.generate().unwrap();
+    let mut direct = SimpleNBody::new(&ps).unwrap();
+    let mut tree = BarnesHut::new(&ps).unwrap();
+    direct.set_timestep(1. * Units::yr).unwrap();
+    tree.set_timestep(1. * Units::yr).unwrap();
+
+    direct.evolve(&(10. * Units::yr)).unwrap();
+    tree.evolve(&(10. * Units::yr)).unwrap();
+
+    for state in [direct.get_state().unwrap(), tree.get_state().unwrap()].iter() {
+        for (before, after) in ps.particles.iter().zip(state.particles.iter()) {
+            assert_eq!(before.get_id(), after.get_id());
+        }
+    }
+}
+
+#[test]
+fn particle_set_center_of_mass() -> Result<(), Error> {
+    let mut ps = particle_set()?;
+    ps.add_particle(Particle::new(
+        Vector3::new(-1., 2., 0.) * Units::pc,
+        Vector3::new(1., -3., 0.) * Units::kms,
+        3. * Units::MSun,
+    )?);
+
+    let (r, v) = ps.get_center_of_mass()?;
+
+    assert_eq!(ps.get_total_mass(), 4. * Units::MSun);
+    assert!((r - Vector3::new(-0.5, 1.5, 0.) * Units::pc).mag().value_in(Units::pc) < 1e-15);
+    assert!((v - Vector3::new(0.75, -2., 0.) * Units::kms).mag().value_in(Units::kms) < 1e-15);
+
+    ps.move_to_center_of_mass()?;
+    let (r, v) = ps.get_center_of_mass()?;
+
+    assert!(r.mag().value_in(Units::pc) < 1e-15);
+    assert!(v.mag().value_in(Units::kms) < 1e-15);
+    assert!(ps.shift(Vector3::null_vector() * Units::kms, Vector3::null_vector() * Units::kms).is_err());
+    assert!(matches!(ParticleSet::new()?.get_center_of_mass(), Err(Error::EmptySet)));
+
+    Ok(())
+}
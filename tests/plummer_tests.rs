@@ -0,0 +1,137 @@
+mod common;
+
+use xbody_model::error::Error;
+use xbody_model::generators::{plummer::Plummer, Generator};
+use xbody_model::quantity::Units;
+
+fn analytic_mass_fraction(r: f64, a: f64) -> f64 {
+    r.powi(3) / (r.powi(2) + a.powi(2)).powf(1.5)
+}
+
+/// Ratio of mean `q^2` of speeds `q v_esc` capped at `q_max` and uncapped ones, for the distribution `q^2 (1 - q^2)^3.5`.
+fn capped_dispersion_ratio(q_max: f64) -> f64 {
+    let mean_square = |q_max: f64| -> f64 {
+        let n = 10000;
+        let (mut moment, mut norm) = (0., 0.);
+
+        for i in 0..n {
+            let q = (i as f64 + 0.5) / n as f64 * q_max;
+            let g = q.powi(2) * (1. - q.powi(2)).powf(3.5);
+
+            moment += q.powi(2) * g;
+            norm += g;
+        }
+
+        moment / norm
+    };
+
+    mean_square(q_max) / mean_square(1.)
+}
+
+#[test]
+fn plummer_cumulative_mass_profile() -> Result<(), Error> {
+    let n = 5000;
+    let mut plummer = Plummer::new(1. * Units::pc, n, 1e+4 * Units::MSun)?;
+    plummer.set_truncation_radius(5. * Units::pc)?;
+    let ps = plummer.generate_with_seed(1)?;
+    let radii: Vec<f64> = ps.particles.iter().map(|p| p.get_position().mag().value_in(Units::pc)).collect();
+
+    assert_eq!(radii.len(), n);
+    assert!(radii.iter().all(|r| *r <= 5.));
+
+    for r in [0.3, 0.7, 1., 1.5, 2.5, 4.] {
+        let expected = analytic_mass_fraction(r, 1.) / analytic_mass_fraction(5., 1.);
+        common::assert_cumulative_fraction(&radii, r, expected);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn plummer_virial_equilibrium() -> Result<(), Error> {
+    let mut plummer = Plummer::new(1. * Units::pc, 2000, 1e+4 * Units::MSun)?;
+    plummer.set_truncation_radius(f64::INFINITY * Units::pc)?;
+    let ps = plummer.generate_with_seed(2)?;
+
+    let kinetic = ps.get_kinetic_energy();
+    let potential = ps.get_potential_energy();
+    let ratio = (2. * kinetic).value_in_q(-1. * potential);
+
+    assert!((ratio - 1.).abs() < 0.1);
+
+    // analytic potential energy of Plummer sphere is -3 pi G M^2 / (32 a)
+    let expected = -3. * std::f64::consts::PI / 32. * Units::G * (1e+4 * Units::MSun).pow(2.) / (1. * Units::pc);
+
+    assert!((potential.value_in_q(expected) - 1.).abs() < 0.1);
+
+    Ok(())
+}
+
+#[test]
+fn plummer_truncated_virial_equilibrium() -> Result<(), Error> {
+    for truncation in [1_f64, 5.] {
+        let mut plummer = Plummer::new(1. * Units::pc, 2000, 1e+4 * Units::MSun)?;
+        plummer.set_truncation_radius(truncation * Units::pc)?;
+        let ps = plummer.generate_with_seed(3)?;
+
+        // pressure at the truncation radius of the untruncated sphere whose inner part the particles are:
+        // `4 π r_t^3 p(r_t) = x^3 / (2 (1 + x^2)^3) G M^2 / a`, reduced by the cap of speeds there at `q_max`
+        let fraction = analytic_mass_fraction(truncation, 1.);
+        let q_max = (fraction * (1. + truncation.powi(2)).sqrt() / truncation).sqrt();
+        let mass = 1e+4 / fraction * Units::MSun;
+        let surface = capped_dispersion_ratio(q_max) * truncation.powi(3) / (2. * (1. + truncation.powi(2)).powi(3))
+            * Units::G
+            * mass.pow(2.)
+            / (1. * Units::pc);
+        let kinetic = ps.get_kinetic_energy();
+        let potential = ps.get_potential_energy();
+
+        assert!(((2. * kinetic - surface).value_in_q(-1. * potential) - 1.).abs() < 0.1);
+
+        if truncation >= 5. {
+            assert!(((2. * kinetic).value_in_q(-1. * potential) - 1.).abs() < 0.1);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn plummer_truncated_particles_are_bound() -> Result<(), Error> {
+    for truncation in [1_f64, 2.] {
+        let mut plummer = Plummer::new(1. * Units::pc, 5000, 1e+4 * Units::MSun)?;
+        plummer.set_truncation_radius(truncation * Units::pc)?;
+        let ps = plummer.generate_with_seed(4)?;
+
+        // potential of the truncated sphere: Plummer one of mass `M` inside `r_t` matched to `-G m / r` outside it
+        let mass = 1e+4 / analytic_mass_fraction(truncation, 1.);
+        let depth = |r: f64| -> f64 {
+            mass / (1. + r.powi(2)).sqrt() - mass / (1. + truncation.powi(2)).sqrt() + 1e+4 / truncation
+        };
+        let unit = (Units::G * Units::MSun.convert() / (1. * Units::pc)).value_in_q(Units::kms.pow(2.));
+
+        assert!(ps.particles.iter().all(|p| {
+            let r = p.get_position().mag().value_in(Units::pc);
+            let v = p.get_velocity().mag().value_in(Units::kms);
+
+            v.powi(2) < 2. * depth(r) * unit
+        }));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn plummer_invalid_parameters() -> Result<(), Error> {
+    assert!(Plummer::new(0. * Units::pc, 10, 1. * Units::MSun).is_err());
+    assert!(Plummer::new(1. * Units::pc, 0, 1. * Units::MSun).is_err());
+    assert!(Plummer::new(1. * Units::pc, 10, 0. * Units::MSun).is_err());
+
+    let mut plummer = Plummer::new(1. * Units::pc, 10, 1. * Units::MSun)?;
+
+    assert!(plummer.set_truncation_radius(0. * Units::pc).is_err());
+    assert!(plummer.set_truncation_radius(1. * Units::MSun).is_err());
+    assert_eq!(plummer.get_truncation_radius().value_in(Units::pc)?, 10.);
+
+    Ok(())
+}
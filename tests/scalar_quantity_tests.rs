@@ -1,5 +1,9 @@
+#![allow(clippy::bool_assert_comparison)]
+
 use xbody_model::quantity::Units;
 use xbody_model::vector::Vector3;
+use xbody_model::Error;
+use std::cmp::Ordering;
 
 #[test]
 fn squantity_add_squantity() {
@@ -303,3 +307,80 @@ fn squantity_cmp_incompatible_squantity() {
 
     let _a = q1 > q2;
 }
+
+#[test]
+fn squantity_checked_add_squantity() {
+    let q1 = 2. * Units::m;
+    let q2 = 3. * Units::m;
+
+    let actual = q1.checked_add(q2).unwrap();
+    let expected = 5. * Units::m;
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn squantity_checked_add_incompatible_squantity() {
+    let q1 = 2. * Units::m;
+    let q2 = 3. * Units::s;
+
+    let actual = q1.checked_add(q2);
+
+    assert!(matches!(actual, Err(Error::IncompatibleUnits { .. })));
+}
+
+#[test]
+fn squantity_checked_sub_squantity() {
+    let q1 = 2. * Units::m;
+    let q2 = 3. * Units::m;
+
+    let actual = q1.checked_sub(q2).unwrap();
+    let expected = -1. * Units::m;
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn squantity_checked_sub_incompatible_squantity() {
+    let q1 = 2. * Units::m;
+    let q2 = 3. * Units::s;
+
+    let actual = q1.checked_sub(q2);
+
+    assert!(matches!(actual, Err(Error::IncompatibleUnits { .. })));
+}
+
+#[test]
+fn squantity_checked_cmp_squantity() {
+    let q1 = 2.0 * Units::m;
+    let q2 = 3.0 * Units::m;
+
+    assert_eq!(q1.checked_cmp(&q2).unwrap(), Some(Ordering::Less));
+    assert_eq!(q2.checked_cmp(&q1).unwrap(), Some(Ordering::Greater));
+    assert_eq!(q1.checked_cmp(&q1).unwrap(), Some(Ordering::Equal));
+}
+
+#[test]
+fn squantity_checked_cmp_incompatible_squantity() {
+    let q1 = 2.0 * Units::m;
+    let q2 = 3.0 * Units::s;
+
+    assert!(q1.checked_cmp(&q2).is_err());
+}
+
+#[test]
+fn squantity_try_value_in() {
+    let q = 2. * Units::kpc;
+
+    let actual = q.try_value_in(Units::pc).unwrap();
+    let expected = 2000.;
+
+    assert!((actual - expected).abs() < 1e-9);
+}
+
+#[test]
+fn squantity_try_value_in_incompatible() {
+    let q = 2. * Units::kpc;
+
+    assert!(q.try_value_in(Units::s).is_err());
+}
@@ -0,0 +1,323 @@
+use xbody_model::error::Error;
+use xbody_model::generators::{plummer::Plummer, Generator};
+use xbody_model::integrators::scheme::Scheme;
+use xbody_model::integrators::simple_nbody::{SimpleNBody, Summation};
+use xbody_model::integrators::timestep::{AdaptiveTimestep, TimestepCriterion};
+use xbody_model::integrators::Integrator;
+use xbody_model::particles::{Particle, ParticleSet};
+use xbody_model::quantity::Units;
+use xbody_model::softening::{Softening, SofteningKernel};
+use xbody_model::vector::Vector3;
+
+/// Sun and Earth on a circular orbit of 1 AU.
+fn sun_and_earth() -> ParticleSet {
+    let mut ps = ParticleSet::new().unwrap();
+    let v = (Units::G * Units::MSun.convert() / Units::AU.convert()).pow(0.5);
+
+    ps.add_particle(
+        Particle::new(
+            Vector3::null_vector() * Units::AU,
+            Vector3::null_vector() * Units::kms,
+            1. * Units::MSun,
+        )
+        .unwrap(),
+    );
+    ps.add_particle(
+        Particle::new(
+            Vector3::new(1., 0., 0.) * Units::AU,
+            Vector3::new(0., 1., 0.) * v,
+            1e-10 * Units::MSun,
+        )
+        .unwrap(),
+    );
+
+    ps
+}
+
+/// Maximal deviation of the Earth from the circular orbit during one year.
+fn max_radius_error(scheme: Scheme) -> f64 {
+    let ps = sun_and_earth();
+    let mut integrator = SimpleNBody::with_scheme(&ps, scheme).unwrap();
+    integrator.set_timestep(5. * Units::day).unwrap();
+    let mut error: f64 = 0.;
+
+    for i in 1..=12 {
+        integrator.evolve(&(i as f64 * Units::month)).unwrap();
+
+        let state = integrator.get_state().unwrap();
+        let d = state.particles[1].get_position() - state.particles[0].get_position();
+        error = error.max((d.mag().value_in(Units::AU) - 1.).abs());
+    }
+
+    error
+}
+
+#[test]
+fn simple_nbody_default_scheme() {
+    let ps = sun_and_earth();
+    let integrator = SimpleNBody::new(&ps).unwrap();
+
+    assert_eq!(integrator.get_scheme(), Scheme::Euler);
+}
+
+#[test]
+fn simple_nbody_schemes_order() {
+    let euler = max_radius_error(Scheme::Euler);
+    let leapfrog = max_radius_error(Scheme::LeapfrogKDK);
+    let verlet = max_radius_error(Scheme::VelocityVerlet);
+    let rk4 = max_radius_error(Scheme::RungeKutta4);
+
+    assert!(leapfrog < euler);
+    assert!((leapfrog - verlet).abs() < 1e-9);
+    assert!(rk4 < leapfrog);
+}
+
+fn total_momentum(ps: &ParticleSet) -> Vector3 {
+    let mut momentum = Vector3::null_vector();
+
+    for p in ps.particles.iter() {
+        momentum += p.get_mass().value_in(Units::MSun) * p.get_velocity().value_in(Units::kms);
+    }
+
+    momentum
+}
+
+/// Two particles at the origin moving in opposite directions and the third one at rest at 1 AU.
+fn overlapping_three_bodies() -> ParticleSet {
+    let mut ps = ParticleSet::new().unwrap();
+    let bodies = [
+        (Vector3::null_vector(), Vector3::new(0., 1., 0.)),
+        (Vector3::null_vector(), Vector3::new(0., -1., 0.)),
+        (Vector3::new(1., 0., 0.), Vector3::null_vector()),
+    ];
+
+    for (position, velocity) in bodies.iter() {
+        ps.add_particle(
+            Particle::new(*position * Units::AU, *velocity * Units::kms, 1. * Units::MSun).unwrap(),
+        );
+    }
+
+    ps
+}
+
+#[test]
+fn simple_nbody_overlapping_particles_without_softening() {
+    let ps = overlapping_three_bodies();
+    let mut integrator = SimpleNBody::new(&ps).unwrap();
+    integrator.set_timestep(1. * Units::day).unwrap();
+
+    match integrator.evolve(&(10. * Units::day)) {
+        Err(Error::NumericalFailure(_)) => {}
+        other => panic!("expected numerical failure, got {:?}", other),
+    }
+}
+
+#[test]
+fn simple_nbody_overlapping_particles_with_zero_softening_length() {
+    let ps = overlapping_three_bodies();
+    let mut integrator = SimpleNBody::new(&ps).unwrap();
+    integrator.set_timestep(1. * Units::day).unwrap();
+    integrator.set_softening(Softening::new(SofteningKernel::Plummer, 0. * Units::AU).unwrap());
+
+    match integrator.evolve(&(10. * Units::day)) {
+        Err(Error::NumericalFailure(_)) => {}
+        other => panic!("expected numerical failure, got {:?}", other),
+    }
+}
+
+#[test]
+fn simple_nbody_overlapping_particles_with_softening() {
+    let ps = overlapping_three_bodies();
+    let eps = 0.01 * Units::AU;
+    let mut integrator = SimpleNBody::with_scheme(&ps, Scheme::LeapfrogKDK).unwrap();
+    integrator.set_timestep(1. * Units::day).unwrap();
+    integrator.set_softening(Softening::new(SofteningKernel::Plummer, eps).unwrap());
+
+    // overlapping particles do not pull each other, but both of them pull the third one
+    let acceleration = Units::ms.convert() / Units::s;
+    let a: Vec<Vector3> = integrator
+        .get_accelerations()
+        .iter()
+        .map(|a| a.value_in_q(acceleration))
+        .collect();
+    let d = 1. * Units::AU;
+    let expected = (2. * Units::G * Units::MSun * d / (d.pow(2.) + eps.pow(2.)).pow(1.5))
+        .value_in_q(acceleration);
+
+    assert_eq!(a[0], a[1]);
+    assert!((-a[2].x / expected - 1.).abs() < 1e-12);
+
+    integrator.evolve(&(10. * Units::day)).unwrap();
+
+    let state = integrator.get_state().unwrap();
+
+    for p in state.particles.iter() {
+        assert!(p.get_position().value_in(Units::AU).mag().is_finite());
+    }
+
+    // overlapping particles were moving in opposite directions, so they have to move symmetrically
+    let p0 = state.particles[0].get_position().value_in(Units::AU);
+    let p1 = state.particles[1].get_position().value_in(Units::AU);
+
+    assert!((p0.y + p1.y).abs() < 1e-12);
+    assert!((p0.x - p1.x).abs() < 1e-12);
+    assert!(total_momentum(&state).mag() < 1e-12);
+}
+
+#[test]
+fn simple_nbody_default_summation() {
+    let ps = sun_and_earth();
+    let integrator = SimpleNBody::new(&ps).unwrap();
+
+    assert_eq!(integrator.get_summation(), Summation::Pairwise);
+}
+
+#[test]
+fn simple_nbody_summations_agree() {
+    let ps = Plummer::new(1. * Units::pc, 100, 1e+3 * Units::MSun)
+        .unwrap()
+        .generate_with_seed(1)
+        .unwrap();
+    let mut integrator = SimpleNBody::new(&ps).unwrap();
+    let pairwise = integrator.get_accelerations();
+    integrator.set_summation(Summation::PerParticle);
+    let per_particle = integrator.get_accelerations();
+
+    for i in 0..pairwise.len() {
+        let error = (pairwise[i] - per_particle[i]).mag() / per_particle[i].mag();
+
+        assert!(error.value_in_q(Units::s.convert() / Units::s) < 1e-10);
+    }
+}
+
+#[test]
+fn simple_nbody_pairwise_conserves_momentum() {
+    let ps = Plummer::new(1. * Units::pc, 100, 1e+3 * Units::MSun)
+        .unwrap()
+        .generate_with_seed(1)
+        .unwrap();
+    let mut scale = 0.;
+
+    for p in ps.particles.iter() {
+        scale += p.get_mass().value_in(Units::MSun) * p.get_velocity().value_in(Units::kms).mag();
+    }
+
+    let mut integrator = SimpleNBody::with_scheme(&ps, Scheme::LeapfrogKDK).unwrap();
+    integrator.set_timestep(0.01 * Units::Myr).unwrap();
+    integrator.set_softening(Softening::new(SofteningKernel::Plummer, 0.01 * Units::pc).unwrap());
+    integrator.evolve(&(1. * Units::Myr)).unwrap();
+
+    let change = total_momentum(&integrator.get_state().unwrap()) - total_momentum(&ps);
+
+    assert!(change.mag() / scale < 1e-13);
+}
+
+#[test]
+fn simple_nbody_timestep_must_be_positive() {
+    let mut integrator = SimpleNBody::new(&sun_and_earth()).unwrap();
+
+    assert!(integrator.set_timestep(0. * Units::s).is_err());
+    assert!(integrator.set_timestep(-1. * Units::s).is_err());
+    assert!(integrator.set_timestep(f64::NAN * Units::s).is_err());
+    assert!(integrator.set_timestep(f64::INFINITY * Units::s).is_err());
+    assert!(integrator.set_timestep(1. * Units::m).is_err());
+    assert!(integrator.set_timestep(1. * Units::s).is_ok());
+}
+
+#[test]
+fn adaptive_timestep_parameters() {
+    assert!(AdaptiveTimestep::new(TimestepCriterion::Aarseth, 0., 1. * Units::s, 2. * Units::s).is_err());
+    assert!(AdaptiveTimestep::new(TimestepCriterion::Aarseth, 0.1, 0. * Units::s, 2. * Units::s).is_err());
+    assert!(AdaptiveTimestep::new(TimestepCriterion::Aarseth, 0.1, 2. * Units::s, 1. * Units::s).is_err());
+    assert!(AdaptiveTimestep::new(TimestepCriterion::Aarseth, 0.1, 1. * Units::m, 2. * Units::s).is_err());
+    assert!(AdaptiveTimestep::new(TimestepCriterion::Aarseth, 0.1, 1. * Units::s, 1. * Units::s).is_ok());
+}
+
+#[test]
+fn adaptive_timestep_lands_on_end_time() {
+    let mut ps = ParticleSet::new().unwrap();
+    ps.add_particle(
+        Particle::new(
+            Vector3::null_vector() * Units::m,
+            Vector3::new(1., 0., 0.) * Units::ms,
+            1. * Units::kg,
+        )
+        .unwrap(),
+    );
+    let adaptive =
+        AdaptiveTimestep::new(TimestepCriterion::FreeFall, 0.1, 0.1 * Units::s, 1. * Units::s).unwrap();
+    let mut integrator = SimpleNBody::new(&ps).unwrap();
+    integrator.set_adaptive_timestep(adaptive);
+    integrator.evolve(&(10.5 * Units::s)).unwrap();
+
+    let x = integrator.get_state().unwrap().particles[0].get_position().value_in(Units::m).x;
+
+    assert!((x - 10.5).abs() < 1e-12);
+    assert!((integrator.get_timestep().value_in(Units::s) - 0.5).abs() < 1e-12);
+    assert_eq!(integrator.get_adaptive_timestep(), Some(adaptive));
+
+    integrator.set_timestep(1. * Units::s).unwrap();
+
+    assert_eq!(integrator.get_adaptive_timestep(), None);
+}
+
+#[test]
+fn adaptive_timestep_acceleration_requires_softening() {
+    let ps = sun_and_earth();
+    let mut integrator = SimpleNBody::new(&ps).unwrap();
+    integrator.set_adaptive_timestep(
+        AdaptiveTimestep::new(TimestepCriterion::Acceleration, 0.1, 1. * Units::s, 1. * Units::day).unwrap(),
+    );
+
+    assert!(matches!(
+        integrator.evolve(&(1. * Units::day)),
+        Err(Error::InvalidParameter { .. })
+    ));
+}
+
+/// Distance between the initial and the final position of the planet on the orbit with eccentricity 0.9 after one period.
+fn eccentric_orbit_error(adaptive: Option<AdaptiveTimestep>) -> f64 {
+    let e = 0.9;
+    let mut ps = ParticleSet::new().unwrap();
+    let v = (Units::G * Units::MSun.convert() / Units::AU.convert() * (1. - e) / (1. + e)).pow(0.5);
+
+    ps.add_particle(
+        Particle::new(
+            Vector3::null_vector() * Units::AU,
+            Vector3::null_vector() * Units::kms,
+            1. * Units::MSun,
+        )
+        .unwrap(),
+    );
+    ps.add_particle(
+        Particle::new(Vector3::new(1. + e, 0., 0.) * Units::AU, Vector3::new(0., 1., 0.) * v, 1e-10 * Units::MSun)
+            .unwrap(),
+    );
+
+    let mut integrator = SimpleNBody::with_scheme(&ps, Scheme::LeapfrogKDK).unwrap();
+    integrator.set_timestep(1. * Units::day).unwrap();
+
+    if let Some(adaptive) = adaptive {
+        integrator.set_adaptive_timestep(adaptive);
+    }
+
+    integrator.evolve(&(1. * Units::yr)).unwrap();
+
+    let state = integrator.get_state().unwrap();
+    let d = state.particles[1].get_position() - state.particles[0].get_position();
+
+    (d.value_in(Units::AU) - Vector3::new(1. + e, 0., 0.)).mag()
+}
+
+#[test]
+fn adaptive_timestep_criteria() {
+    let fixed = eccentric_orbit_error(None);
+    let period = 1. * Units::yr;
+
+    for criterion in [TimestepCriterion::Aarseth, TimestepCriterion::FreeFall].iter() {
+        let adaptive = AdaptiveTimestep::new(*criterion, 0.05, 1e-6 * period, 0.01 * period).unwrap();
+        let error = eccentric_orbit_error(Some(adaptive));
+
+        assert!(error < fixed / 10.);
+    }
+}
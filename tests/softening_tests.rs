@@ -0,0 +1,94 @@
+use xbody_model::integrators::simple_nbody::SimpleNBody;
+use xbody_model::particles::{Particle, ParticleSet};
+use xbody_model::quantity::Units;
+use xbody_model::softening::{Softening, SofteningKernel};
+use xbody_model::vector::Vector3;
+
+fn two_particles(distance: f64) -> ParticleSet {
+    let mut ps = ParticleSet::new().unwrap();
+
+    for x in [0., distance].iter() {
+        ps.add_particle(
+            Particle::new(
+                Vector3::new(*x, 0., 0.) * Units::pc,
+                Vector3::null_vector() * Units::kms,
+                1. * Units::MSun,
+            )
+            .unwrap(),
+        );
+    }
+
+    ps
+}
+
+#[test]
+fn softening_incompatible_length() {
+    let actual = Softening::new(SofteningKernel::Plummer, 1. * Units::s);
+
+    assert!(actual.is_err());
+}
+
+#[test]
+fn softening_negative_length() {
+    let actual = Softening::new(SofteningKernel::Plummer, -1. * Units::pc);
+
+    assert!(actual.is_err());
+}
+
+#[test]
+fn plummer_softened_potential_energy() {
+    let ps = two_particles(3.);
+    let softening = Softening::new(SofteningKernel::Plummer, 4. * Units::pc).unwrap();
+
+    let actual = ps.get_softened_potential_energy(&softening) / ps.get_potential_energy();
+    let expected = 3. / 5.;
+
+    assert!((actual.value_in_q(1. * Units::m / Units::m) - expected).abs() < 1e-12);
+}
+
+#[test]
+fn spline_is_newtonian_beyond_support() {
+    let ps = two_particles(3.);
+    let softening = Softening::new(SofteningKernel::Spline, 1. * Units::pc).unwrap();
+
+    let actual = ps.get_softened_potential_energy(&softening);
+    let expected = ps.get_potential_energy();
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn spline_matches_plummer_at_zero_distance() {
+    let ps = two_particles(0.);
+    let plummer = Softening::new(SofteningKernel::Plummer, 1. * Units::pc).unwrap();
+    let spline = Softening::new(SofteningKernel::Spline, 1. * Units::pc).unwrap();
+
+    let actual = ps.get_softened_potential_energy(&spline) / ps.get_softened_potential_energy(&plummer);
+
+    assert!((actual.value_in_q(1. * Units::m / Units::m) - 1.).abs() < 1e-9);
+}
+
+#[test]
+fn simple_nbody_forces_are_softened() {
+    // at distance much smaller than the softening length the acceleration is linear in distance:
+    // `G m d / eps^3` for Plummer kernel and `32 / 3 G m d / (2.8 eps)^3` for the spline one
+    let distance = 1e-3;
+    let ps = two_particles(distance);
+    let unit = Units::G * Units::MSun.convert() / Units::pc.convert().pow(2.);
+    let kernels = [
+        (SofteningKernel::Plummer, distance),
+        (SofteningKernel::Spline, 32. / 3. * distance / 2.8_f64.powi(3)),
+    ];
+
+    for (kernel, expected) in kernels.iter() {
+        let mut integrator = SimpleNBody::new(&ps).unwrap();
+        integrator.set_softening(Softening::new(*kernel, 1. * Units::pc).unwrap());
+
+        let accelerations = integrator.get_accelerations();
+        let actual = (accelerations[0].mag() / unit).value_in_q(1. * Units::m / Units::m);
+        let imbalance = ((accelerations[0] + accelerations[1]).mag() / unit).value_in_q(1. * Units::m / Units::m);
+
+        assert!((actual / expected - 1.).abs() < 1e-5);
+        assert!(imbalance < 1e-12 * expected);
+    }
+}
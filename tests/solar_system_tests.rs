@@ -0,0 +1,168 @@
+use xbody_model::generators::solar_system::{Body, SolarSystem};
+use xbody_model::generators::Generator;
+use xbody_model::integrators::ias15::IAS15;
+use xbody_model::integrators::Integrator;
+use xbody_model::particles::ParticleSet;
+use xbody_model::quantity::Units;
+use xbody_model::Error;
+
+fn energy(ps: &ParticleSet) -> f64 {
+    (ps.get_kinetic_energy() + ps.get_potential_energy()).value_in(Units::J)
+}
+
+#[test]
+fn solar_system_default() -> Result<(), Error> {
+    let ps = SolarSystem::new().generate()?;
+
+    assert_eq!(ps.particles.len(), 9);
+    assert_eq!(ps.particles[0].get_position().mag().value_in(Units::AU), 0.);
+    assert!((ps.get_total_mass().value_in_q(ps.particles[0].get_mass()) - 1.00134).abs() < 1e-5);
+
+    let earth = &ps.particles[3];
+    assert!((earth.get_position().mag().value_in(Units::AU) - 0.983).abs() < 1e-3);
+    assert!((earth.get_velocity().mag().value_in(Units::kms) - 30.3).abs() < 0.1);
+
+    let jupiter = &ps.particles[5];
+    assert!((jupiter.get_position().mag().value_in(Units::AU) - 4.965).abs() < 1e-2);
+
+    Ok(())
+}
+
+#[test]
+fn solar_system_bodies() -> Result<(), Error> {
+    let mut generator = SolarSystem::new();
+    generator.set_bodies(&[Body::Sun, Body::Earth, Body::Moon, Body::Pluto])?;
+    generator.set_barycentric(true);
+    let ps = generator.generate()?;
+
+    let (r, v) = ps.get_center_of_mass()?;
+    assert!(r.mag().value_in(Units::AU) < 1e-12);
+    assert!(v.mag().value_in(Units::kms) < 1e-12);
+
+    let (earth, moon) = (&ps.particles[1], &ps.particles[2]);
+    let distance = (moon.get_position() - earth.get_position()).mag().value_in(Units::m) / 1e3;
+    assert!((distance - 4.0e5).abs() < 2e4);
+
+    // the Earth without the Moon is placed at their barycentre
+    let mut generator = SolarSystem::new();
+    generator.set_bodies(&[Body::Sun, Body::Earth])?;
+    let ps_earth = generator.generate()?;
+    generator.set_bodies(&[Body::Sun, Body::Earth, Body::Moon])?;
+    let ps_moon = generator.generate()?;
+
+    let barycenter = (ps_moon.particles[1].get_position() * ps_moon.particles[1].get_mass()
+        + ps_moon.particles[2].get_position() * ps_moon.particles[2].get_mass())
+        / (ps_moon.particles[1].get_mass() + ps_moon.particles[2].get_mass());
+    assert!((barycenter - ps_earth.particles[1].get_position()).mag().value_in(Units::m) < 1e-3);
+    assert_eq!(ps_earth.get_total_mass(), ps_moon.get_total_mass());
+
+    assert!(generator.set_bodies(&[]).is_err());
+    assert!(generator.set_bodies(&[Body::Sun, Body::Sun]).is_err());
+    assert!(generator.set_bodies(&[Body::Sun, Body::Moon]).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn solar_system_satellites() -> Result<(), Error> {
+    let mut generator = SolarSystem::new();
+    generator.set_bodies(&[Body::Sun, Body::Jupiter, Body::Saturn])?;
+    let planets = generator.generate()?;
+
+    let bodies = [
+        Body::Sun,
+        Body::Jupiter,
+        Body::Io,
+        Body::Europa,
+        Body::Ganymede,
+        Body::Callisto,
+        Body::Saturn,
+        Body::Titan,
+    ];
+    generator.set_bodies(&bodies)?;
+    let ps = generator.generate()?;
+
+    assert_eq!(Body::satellites().len(), 6);
+    assert_eq!(Body::Callisto.planet(), Some(Body::Jupiter));
+    assert_eq!(Body::Jupiter.planet(), None);
+
+    // the planets with their satellites are at the barycentres of their systems
+    for (planet, satellites, barycentre) in [(1, 2..6, 1), (6, 7..8, 2)] {
+        let mut momentum = ps.particles[planet].get_position() * ps.particles[planet].get_mass();
+        let mut mass = ps.particles[planet].get_mass();
+
+        for i in satellites {
+            momentum += ps.particles[i].get_position() * ps.particles[i].get_mass();
+            mass += ps.particles[i].get_mass();
+        }
+
+        let expected = planets.particles[barycentre].get_position();
+
+        assert!((momentum / mass - expected).mag().value_in(Units::m) < 1.);
+    }
+
+    assert!((ps.get_total_mass().value_in_q(planets.get_total_mass()) - 1.).abs() < 1e-15);
+
+    // semi-major axes (km) of the satellites and their planets
+    let orbits = [(2, 1, 421800.), (3, 1, 671100.), (4, 1, 1070400.), (5, 1, 1882700.), (7, 6, 1221870.)];
+    let distances = |ps: &ParticleSet| -> Vec<f64> {
+        orbits
+            .iter()
+            .map(|(i, planet, _)| {
+                (ps.particles[*i].get_position() - ps.particles[*planet].get_position()).mag().value_in(Units::m) / 1e3
+            })
+            .collect()
+    };
+
+    for (distance, (_, _, a)) in distances(&ps).iter().zip(orbits.iter()) {
+        assert!((distance / a - 1.).abs() < 0.05);
+    }
+
+    // the satellites stay on their orbits for a few of their periods
+    let mut integrator = IAS15::new(&ps)?;
+    integrator.evolve(&(50. * Units::day))?;
+    let state = integrator.get_state()?;
+
+    assert!(((energy(&state) - energy(&ps)) / energy(&ps)).abs() < 1e-12);
+
+    for (distance, (_, _, a)) in distances(&state).iter().zip(orbits.iter()) {
+        assert!((distance / a - 1.).abs() < 0.1);
+    }
+
+    assert!(generator.set_bodies(&[Body::Sun, Body::Io]).is_err());
+    assert!(generator.set_bodies(&[Body::Sun, Body::Jupiter, Body::Titan]).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn solar_system_regression() -> Result<(), Error> {
+    let mut generator = SolarSystem::new();
+    generator.set_bodies(&[
+        Body::Sun,
+        Body::Mercury,
+        Body::Venus,
+        Body::Earth,
+        Body::Moon,
+        Body::Mars,
+        Body::Jupiter,
+        Body::Saturn,
+        Body::Uranus,
+        Body::Neptune,
+        Body::Pluto,
+    ])?;
+    generator.set_barycentric(true);
+    let ps = generator.generate()?;
+
+    let mut integrator = IAS15::new(&ps)?;
+    integrator.evolve(&(1. * Units::yr))?;
+    let state = integrator.get_state()?;
+
+    assert!(((energy(&state) - energy(&ps)) / energy(&ps)).abs() < 1e-12);
+
+    // the Earth is close to its initial position after one year
+    let shift = (state.particles[3].get_position() - ps.particles[3].get_position()).mag();
+    assert!(shift.value_in(Units::AU) < 0.03);
+
+    Ok(())
+}
@@ -0,0 +1,160 @@
+mod common;
+
+use xbody_model::error::Error;
+use xbody_model::generators::{spherical::Spherical, Generator};
+use xbody_model::quantity::Units;
+
+fn hernquist(x: f64) -> f64 {
+    1. / (x * (1. + x).powi(3))
+}
+
+fn plummer(x: f64) -> f64 {
+    (1. + x.powi(2)).powf(-2.5)
+}
+
+#[test]
+fn spherical_hernquist_profile() -> Result<(), Error> {
+    // isotropic velocity dispersion of Hernquist model (Hernquist, 1990, eq. 10) in the units of G M / a
+    let dispersion = |r: f64| -> f64 {
+        (12. * r * (r + 1.).powi(3) * ((r + 1.) / r).ln()
+            - r / (r + 1.) * (25. + 52. * r + 42. * r.powi(2) + 12. * r.powi(3)))
+            / 12.
+    };
+    let n = 20000;
+    let ps = Spherical::new(hernquist, 1. * Units::pc, n, 1e+4 * Units::MSun)?.generate_with_seed(1)?;
+    let unit = (Units::G * 1e+4 * Units::MSun / (1. * Units::pc)).value_in_q(Units::kms.pow(2.));
+    let radii: Vec<f64> = ps.particles.iter().map(|p| p.get_position().mag().value_in(Units::pc)).collect();
+
+    assert!((ps.get_total_mass().value_in(Units::MSun) / 1e+4 - 1.).abs() < 1e-12);
+
+    for r in [0.1_f64, 0.5, 1., 3., 10.] {
+        let expected = (r / (r + 1.)).powi(2);
+        common::assert_cumulative_fraction(&radii, r, expected);
+    }
+
+    let mut ratio = 0.;
+    let mut count = 0;
+
+    for p in ps.particles.iter() {
+        let r = p.get_position().mag().value_in(Units::pc);
+        let v = p.get_velocity().mag().value_in(Units::kms);
+
+        if (0.3..5.).contains(&r) {
+            ratio += v.powi(2) / (3. * unit * dispersion(r));
+            count += 1;
+        }
+    }
+
+    assert!((ratio / count as f64 - 1.).abs() < 0.03);
+
+    Ok(())
+}
+
+#[test]
+fn spherical_osipkov_merritt_anisotropy() -> Result<(), Error> {
+    let mut spherical = Spherical::new(hernquist, 1. * Units::pc, 20000, 1e+4 * Units::MSun)?;
+    spherical.set_anisotropy_radius(1. * Units::pc)?;
+
+    let ps = spherical.generate_with_seed(2)?;
+
+    for (inner, outer) in [(0., 0.7), (0.7, 2.), (2., 10.)] {
+        let mut radial = 0.;
+        let mut tangential = 0.;
+        let mut expected = 0.;
+        let mut count = 0;
+
+        for p in ps.particles.iter() {
+            let position = p.get_position().value_in(Units::pc);
+            let velocity = p.get_velocity().value_in(Units::kms);
+            let r = position.mag();
+
+            if (inner..outer).contains(&r) {
+                let v = velocity.mag();
+                let v_r = velocity.dot(&position) / r;
+
+                radial += v_r.powi(2);
+                tangential += v.powi(2) - v_r.powi(2);
+                expected += r.powi(2) / (r.powi(2) + 1.);
+                count += 1;
+            }
+        }
+
+        let beta = 1. - tangential / (2. * radial);
+
+        assert!((beta - expected / count as f64).abs() < 0.1);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn spherical_external_point_mass() -> Result<(), Error> {
+    // Hernquist model around a point mass of its own mass; the isotropic velocity dispersion in the units of G M / a
+    // is the sum of the one of Hernquist model and of `∫ ρ(s) / s^2 ds / ρ(r)` from `r` to infinity, `s = r / t`
+    let dispersion = |r: f64| -> f64 {
+        let hernquist = (12. * r * (r + 1.).powi(3) * ((r + 1.) / r).ln()
+            - r / (r + 1.) * (25. + 52. * r + 42. * r.powi(2) + 12. * r.powi(3)))
+            / 12.;
+        let steps = 1000;
+        let mut point_mass = 0.;
+
+        for i in 0..steps {
+            let t = (i as f64 + 0.5) / steps as f64;
+            point_mass += t.powi(4) / (t + r).powi(3) / steps as f64;
+        }
+
+        hernquist + point_mass * (1. + r).powi(3) / r
+    };
+    let mut spherical = Spherical::new(hernquist, 1. * Units::pc, 20000, 1e+4 * Units::MSun)?;
+    spherical.set_external_potential(|x| 1. / x);
+
+    let ps = spherical.generate_with_seed(3)?;
+    let unit = (Units::G * 1e+4 * Units::MSun / (1. * Units::pc)).value_in_q(Units::kms.pow(2.));
+    let mut ratio = 0.;
+    let mut count = 0;
+
+    for p in ps.particles.iter() {
+        let r = p.get_position().mag().value_in(Units::pc);
+        let v = p.get_velocity().mag().value_in(Units::kms);
+
+        // every particle is bound by both the model and the point mass
+        assert!(v.powi(2) < 2. * unit * (1. / (1. + r) + 1. / r));
+
+        if (0.1..5.).contains(&r) {
+            ratio += v.powi(2) / (3. * unit * dispersion(r));
+            count += 1;
+        }
+    }
+
+    assert!((ratio / count as f64 - 1.).abs() < 0.03);
+
+    Ok(())
+}
+
+#[test]
+fn spherical_invalid_parameters() -> Result<(), Error> {
+    assert!(Spherical::new(plummer, 0. * Units::pc, 10, 1. * Units::MSun).is_err());
+    assert!(Spherical::new(plummer, 1. * Units::pc, 0, 1. * Units::MSun).is_err());
+    assert!(Spherical::new(plummer, 1. * Units::pc, 10, 0. * Units::MSun).is_err());
+
+    let mut spherical = Spherical::new(plummer, 1. * Units::pc, 10, 1. * Units::MSun)?;
+
+    assert!(spherical.set_radial_range(0., 10.).is_err());
+    assert!(spherical.set_radial_range(10., 1.).is_err());
+    assert!(spherical.set_radial_range(1e-3, f64::INFINITY).is_err());
+    assert!(spherical.set_anisotropy_radius(0. * Units::pc).is_err());
+    assert_eq!(spherical.get_radial_range(), (1e-6, 1e+6));
+
+    // the density must be positive, finite and give finite mass
+    assert!(Spherical::new(|x| 1. - x, 1. * Units::pc, 10, 1. * Units::MSun)?.generate().is_err());
+    assert!(Spherical::new(|_| f64::NAN, 1. * Units::pc, 10, 1. * Units::MSun)?.generate().is_err());
+    assert!(Spherical::new(|x| x.powi(-2), 1. * Units::pc, 10, 1. * Units::MSun)?.generate().is_err());
+    assert!(Spherical::new(|x| x.powi(-4), 1. * Units::pc, 10, 1. * Units::MSun)?.generate().is_err());
+
+    // cored model has no isotropic distribution function around a point mass
+    spherical.set_external_potential(|x| 1. / x);
+
+    assert!(spherical.generate().is_err());
+
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use std::convert::TryFrom;
+use xbody_model::generators::plummer::Plummer;
+use xbody_model::integrators::simple_nbody::SimpleNBody;
+use xbody_model::particles::{Particle, ParticleSet};
+use xbody_model::quantity::typed::{Length, LengthVector, Mass, Time, VelocityVector};
+use xbody_model::quantity::{ScalarQuantity, Units};
+use xbody_model::vector::Vector3;
+
+#[test]
+fn typed_new() {
+    let actual = Length::new(2. * Units::pc).unwrap();
+    let expected = Length::from_si(2. * 3.086e+16);
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn typed_new_incompatible() {
+    let actual = Length::new(2. * Units::s);
+
+    assert!(actual.is_err());
+}
+
+#[test]
+fn typed_try_from_and_into_quantity() {
+    let q = 3. * Units::yr;
+
+    let typed = Time::try_from(q).unwrap();
+    let actual: ScalarQuantity = typed.into();
+
+    assert_eq!(actual, q);
+}
+
+#[test]
+fn typed_arithmetic() {
+    let m1 = Mass::new(1. * Units::kg).unwrap();
+    let m2 = Mass::new(3. * Units::kg).unwrap();
+
+    assert_eq!(m1 + m2, 4. * m1);
+    assert_eq!(m2 - m1, m1 * 2.);
+    assert!(m1 < m2);
+}
+
+#[test]
+fn typed_particle_new() {
+    let position = LengthVector::new(Vector3::new(1., 2., 3.) * Units::pc).unwrap();
+    let velocity = VelocityVector::new(Vector3::new(1., 0., 0.) * Units::kms).unwrap();
+    let mass = Mass::new(1. * Units::MSun).unwrap();
+
+    let actual = Particle::new(position, velocity, mass).unwrap();
+
+    assert_eq!(actual.get_position(), Vector3::new(1., 2., 3.) * Units::pc);
+}
+
+#[test]
+fn typed_plummer_and_timestep() {
+    let r = Length::new(1. * Units::pc).unwrap();
+    let m = Mass::new(1e+3 * Units::MSun).unwrap();
+
+    assert!(Plummer::new(r, 10, m).is_ok());
+    assert!(Plummer::new(1. * Units::pc, 10, 1. * Units::pc).is_err());
+
+    let mut integrator = SimpleNBody::new(&ParticleSet::new().unwrap()).unwrap();
+    integrator.set_timestep(Time::new(1. * Units::yr).unwrap()).unwrap();
+
+    assert_eq!(integrator.get_timestep(), 1. * Units::yr);
+}
@@ -0,0 +1,96 @@
+use xbody_model::integrators::scheme::Scheme;
+use xbody_model::integrators::{simple_nbody::SimpleNBody, Integrator};
+use xbody_model::particles::{Particle, ParticleSet};
+use xbody_model::quantity::systems::{AstroUnits, CGSUnits, NBodyUnits, SIUnits, UnitSystem};
+use xbody_model::quantity::Units;
+use xbody_model::vector::Vector3;
+use xbody_model::Error;
+
+/// Two equal masses on a circular orbit around their center of mass.
+fn circular_binary() -> ParticleSet {
+    let mut ps = ParticleSet::new().unwrap();
+    let m = 1. * Units::MSun;
+    let r = 1. * Units::AU;
+    // each body moves around the center of mass at the distance r / 2
+    let v = (Units::G * m / (2. * r)).pow(0.5);
+
+    for sign in [1., -1.].iter() {
+        ps.add_particle(
+            Particle::new(
+                Vector3::new(*sign * 0.5, 0., 0.) * r,
+                Vector3::new(0., *sign, 0.) * v,
+                m,
+            )
+            .unwrap(),
+        );
+    }
+
+    ps
+}
+
+#[test]
+fn unit_systems_values() {
+    let q = 2. * Units::pc;
+
+    assert!((q.value_in_system(&SIUnits) - 6.172e+16).abs() < 1e+3);
+    assert!((q.value_in_system(&CGSUnits) - 6.172e+18).abs() < 1e+5);
+    assert!((q.value_in_system(&AstroUnits) - 2.).abs() < 1e-12);
+    assert!(((1. * Units::J).value_in_system(&CGSUnits) - 1e+7).abs() < 1e-6);
+}
+
+#[test]
+fn nbody_units_gravitational_constant() {
+    let units = NBodyUnits::new(1e+5 * Units::MSun, 3. * Units::pc).unwrap();
+
+    assert!((units.gravitational_constant() - 1.).abs() < 1e-12);
+    assert!(((1e+5 * Units::MSun).value_in_system(&units) - 1.).abs() < 1e-12);
+}
+
+#[test]
+fn nbody_units_incorrect_parameters() {
+    assert!(NBodyUnits::new(1. * Units::pc, 3. * Units::pc).is_err());
+    assert!(NBodyUnits::new(1. * Units::MSun, -3. * Units::pc).is_err());
+    assert!(matches!(
+        NBodyUnits::from_particle_set(&ParticleSet::new().unwrap()),
+        Err(Error::EmptySet)
+    ));
+
+    let mut single = ParticleSet::new().unwrap();
+    single.add_particle(circular_binary().particles.remove(0));
+
+    assert!(matches!(
+        NBodyUnits::from_particle_set(&single),
+        Err(Error::InvalidParameter { name: "potential energy", .. })
+    ));
+}
+
+#[test]
+fn nbody_units_virial_energy() {
+    let ps = circular_binary();
+    let units = NBodyUnits::from_particle_set(&ps).unwrap();
+
+    let kinetic = ps.get_kinetic_energy().value_in_system(&units);
+    let potential = ps.get_potential_energy().value_in_system(&units);
+    let actual = kinetic + potential;
+
+    assert!((actual + 0.25).abs() < 1e-12);
+}
+
+#[test]
+fn simple_nbody_in_nbody_units() {
+    let ps = circular_binary();
+    let units = NBodyUnits::from_particle_set(&ps).unwrap();
+    let mut si = SimpleNBody::with_scheme(&ps, Scheme::LeapfrogKDK).unwrap();
+    let mut nbody = SimpleNBody::with_units(&ps, Scheme::LeapfrogKDK, units).unwrap();
+    si.set_timestep(1. * Units::day).unwrap();
+    nbody.set_timestep(1. * Units::day).unwrap();
+
+    si.evolve(&(100. * Units::day)).unwrap();
+    nbody.evolve(&(100. * Units::day)).unwrap();
+
+    let expected = si.get_state().unwrap().particles[0].get_position();
+    let actual = nbody.get_state().unwrap().particles[0].get_position();
+
+    assert!((actual - expected).mag().value_in(Units::AU) < 1e-9);
+    assert_eq!(nbody.get_timestep(), si.get_timestep());
+}
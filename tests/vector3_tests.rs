@@ -98,3 +98,14 @@ fn f64_mul_vector3() {
 
     assert_eq!(actual, expected);
 }
+
+#[test]
+fn vector3_cross_vector3() {
+    let v1 = Vector3::new(1., 2., 3.);
+    let v2 = Vector3::new(2., 6., 1.);
+
+    let actual = v1.cross(&v2);
+    let expected = Vector3::new(-16., 5., 2.);
+
+    assert_eq!(actual, expected);
+}
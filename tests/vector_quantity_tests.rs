@@ -143,3 +143,31 @@ fn vquantity_divassign_f64() {
 
     assert_eq!(actual, expected);
 }
+
+#[test]
+fn vquantity_checked_add_vquantity() {
+    let vq1 = Vector3::new(1., 2., 3.) * Units::m;
+    let vq2 = Vector3::new(3., 2., 1.) * Units::m;
+
+    let actual = vq1.checked_add(vq2).unwrap();
+    let expected = Vector3::new(4., 4., 4.) * Units::m;
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn vquantity_checked_sub_incompatible_vquantity() {
+    let vq1 = Vector3::new(1., 2., 3.) * Units::m;
+    let vq2 = Vector3::new(3., 2., 1.) * Units::kg;
+
+    let actual = vq1.checked_sub(vq2);
+
+    assert!(actual.is_err());
+}
+
+#[test]
+fn vquantity_try_value_in_incompatible() {
+    let vq = Vector3::new(1., 2., 3.) * Units::m;
+
+    assert!(vq.try_value_in(Units::ms).is_err());
+}
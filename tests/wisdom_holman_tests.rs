@@ -0,0 +1,158 @@
+use xbody_model::error::Error;
+use xbody_model::integrators::ias15::IAS15;
+use xbody_model::integrators::wisdom_holman::{CentralBody, WisdomHolman};
+use xbody_model::integrators::Integrator;
+use xbody_model::particles::{Particle, ParticleSet};
+use xbody_model::quantity::{ScalarQuantity, Units};
+use xbody_model::vector::Vector3;
+
+/// Planet of mass `m` on circular orbit of radius `r` around the Sun at the origin;
+/// it starts on the positive or negative side of x axis depending on `side`.
+fn planet(r: f64, side: f64, m: f64) -> Particle {
+    let v = (Units::G * Units::MSun.convert() / (r * Units::AU)).pow(0.5);
+
+    Particle::new(
+        Vector3::new(side * r, 0., 0.) * Units::AU,
+        Vector3::new(0., side, 0.) * v,
+        m * Units::MSun,
+    )
+    .unwrap()
+}
+
+/// Sun, Jupiter and Saturn with the Sun in the middle of the set.
+fn outer_planets() -> ParticleSet {
+    let mut ps = ParticleSet::new().unwrap();
+
+    ps.add_particle(planet(5.2, 1., 9.5e-4));
+    ps.add_particle(
+        Particle::new(
+            Vector3::null_vector() * Units::AU,
+            Vector3::null_vector() * Units::kms,
+            1. * Units::MSun,
+        )
+        .unwrap(),
+    );
+    ps.add_particle(planet(9.6, -1., 2.9e-4));
+
+    ps
+}
+
+fn year() -> ScalarQuantity {
+    365.25 * 86400. * Units::s
+}
+
+fn energy(ps: &ParticleSet) -> f64 {
+    (ps.get_kinetic_energy() + ps.get_potential_energy()).value_in(Units::J)
+}
+
+fn max_distance(a: &ParticleSet, b: &ParticleSet) -> f64 {
+    (0..a.particles.len())
+        .map(|i| (a.particles[i].get_position() - b.particles[i].get_position()).mag().value_in(Units::AU))
+        .fold(0., f64::max)
+}
+
+#[test]
+fn wisdom_holman_conserves_energy() {
+    let ps = outer_planets();
+    let mut integrator = WisdomHolman::new(&ps, CentralBody::Index(1)).unwrap();
+    integrator.set_timestep(0.1 * year()).unwrap();
+    integrator.evolve(&(1000. * year())).unwrap();
+
+    let state = integrator.get_state().unwrap();
+    let error = ((energy(&state) - energy(&ps)) / energy(&ps)).abs();
+
+    assert!(error < 1e-5);
+}
+
+#[test]
+fn wisdom_holman_agrees_with_ias15() {
+    let ps = outer_planets();
+    let mut integrator = WisdomHolman::new(&ps, CentralBody::Index(1)).unwrap();
+    integrator.set_timestep(0.01 * year()).unwrap();
+    integrator.evolve(&(100.3 * year())).unwrap();
+
+    let mut reference = IAS15::new(&ps).unwrap();
+    reference.evolve(&(100.3 * year())).unwrap();
+
+    let state = integrator.get_state().unwrap();
+    let expected = reference.get_state().unwrap();
+
+    assert!(max_distance(&state, &expected) < 1e-3);
+
+    for i in 0..ps.particles.len() {
+        assert_eq!(state.particles[i].get_id(), ps.particles[i].get_id());
+    }
+}
+
+#[test]
+fn wisdom_holman_hyperbolic_flyby() {
+    let mut ps = outer_planets();
+    // comet on unbound orbit that passes the Sun far from the plane of the planets
+    ps.add_particle(
+        Particle::new(
+            Vector3::new(-50., 0., 10.) * Units::AU,
+            Vector3::new(10., 0., 0.) * Units::kms,
+            1e-12 * Units::MSun,
+        )
+        .unwrap(),
+    );
+
+    let mut integrator = WisdomHolman::new(&ps, CentralBody::Index(1)).unwrap();
+    integrator.set_timestep(0.01 * year()).unwrap();
+    integrator.evolve(&(50. * year())).unwrap();
+
+    let mut reference = IAS15::new(&ps).unwrap();
+    reference.evolve(&(50. * year())).unwrap();
+
+    let state = integrator.get_state().unwrap();
+    let expected = reference.get_state().unwrap();
+
+    assert!(max_distance(&state, &expected) < 1e-5);
+}
+
+#[test]
+fn wisdom_holman_central_body() -> Result<(), Error> {
+    let ps = outer_planets();
+    let id = ps.particles[1].get_id();
+
+    let mut by_index = WisdomHolman::new(&ps, CentralBody::Index(1))?;
+    let mut by_id = WisdomHolman::new(&ps, CentralBody::Id(id))?;
+    assert_eq!(by_id.get_central_id(), id);
+
+    by_index.set_timestep(0.1 * year())?;
+    by_id.set_timestep(0.1 * year())?;
+    by_index.evolve(&(10. * year()))?;
+    by_id.evolve(&(10. * year()))?;
+
+    assert_eq!(max_distance(&by_index.get_state()?, &by_id.get_state()?), 0.);
+    assert!(WisdomHolman::new(&ps, CentralBody::Index(3)).is_err());
+    assert!(WisdomHolman::new(&ps, CentralBody::Id(u64::MAX)).is_err());
+    assert!(WisdomHolman::new(&ParticleSet::new()?, CentralBody::Index(0)).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn wisdom_holman_parameters() -> Result<(), Error> {
+    let mut ps = outer_planets();
+    let mut integrator = WisdomHolman::new(&ps, CentralBody::Index(1))?;
+
+    assert!(integrator.set_timestep(0. * Units::s).is_err());
+    assert!(integrator.set_timestep(-1. * year()).is_err());
+    assert!(integrator.set_timestep(f64::NAN * Units::s).is_err());
+
+    // body at the position of the central one has no Kepler orbit around it
+    ps.add_particle(Particle::new(
+        Vector3::null_vector() * Units::AU,
+        Vector3::new(0., 1., 0.) * Units::kms,
+        1e-10 * Units::MSun,
+    )?);
+
+    let mut integrator = WisdomHolman::new(&ps, CentralBody::Index(1))?;
+    integrator.set_timestep(0.1 * year())?;
+
+    assert!(integrator.evolve(&(1. * year())).is_err());
+    assert_eq!(max_distance(&integrator.get_state()?, &ps), 0.);
+
+    Ok(())
+}